@@ -3,14 +3,20 @@
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
 use clap::Parser;
-use std::fs::File;
+use std::sync::Arc;
 
-use fastwave_backend::parse_vcd;
+use fastwave_backend::{parse_vcd_from_path_with_options, ParseOptions, ParseProgress};
 
 #[derive(Parser)]
 struct Cli {
     /// The path to the file to read
     path: std::path::PathBuf,
+    /// The number of threads to parse value changes with, 0 for one per core
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+    /// Print how far the parse has got as it goes
+    #[arg(long)]
+    progress: bool,
 }
 
 fn main() -> std::io::Result<()> {
@@ -19,9 +25,24 @@ fn main() -> std::io::Result<()> {
     use std::time::Instant;
 
     let now = Instant::now();
-    let file = File::open(&args.path)?;
-    parse_vcd(file).unwrap();
+    let progress = args.progress.then(|| {
+        Arc::new(|progress: &ParseProgress| {
+            if let Some(total_bytes) = progress.total_bytes {
+                let percent = 100 * progress.bytes_consumed / total_bytes.max(1);
+                eprint!("\r{percent:3}%");
+            }
+        }) as _
+    });
+    let options = ParseOptions {
+        threads: args.threads,
+        progress,
+        ..Default::default()
+    };
+    parse_vcd_from_path_with_options(&args.path, &options).unwrap();
     let elapsed = now.elapsed();
+    if args.progress {
+        eprintln!();
+    }
 
     println!(
         "Parsed VCD file {} : {:.2?}",
@@ -0,0 +1,56 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use clap::Parser;
+use std::process::ExitCode;
+
+use fastwave_backend::{validate_vcd_from_path, Severity};
+
+#[derive(Parser)]
+struct Cli {
+    /// The path to the file to check
+    path: std::path::PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Cli::parse();
+    let path = args.path.display();
+
+    let diagnostics = match validate_vcd_from_path(&args.path) {
+        Ok(diagnostics) => diagnostics,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    for diagnostic in &diagnostics {
+        let severity = match diagnostic.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let message = diagnostic.message();
+        match diagnostic.cursor() {
+            Some(cursor) => println!(
+                "{path}:{}:{}: {severity}: {message}",
+                cursor.line, cursor.column
+            ),
+            None => println!("{path}: {severity}: {message}"),
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    println!("{path}: {errors} error(s), {warnings} warning(s)");
+
+    // warnings are about constructs that most tools read just fine
+    if errors == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -70,11 +70,13 @@ fn main() -> std::io::Result<()> {
     let state_signal = vcd.signal_from_signal_idx(SignalIdx(4));
     let name = state_signal.name();
 
-    let timestamps = vec![31499_000u32, 31500_000u32, 57760_000u32];
+    let timestamps = [31_499_000_u32, 31_500_000_u32, 57_760_000_u32];
     for timestamp in timestamps {
         let time = num::BigUint::from(timestamp);
-        let val = state_signal.query_string_val_on_tmln(&time, &vcd).unwrap();
-        println!("Signal `{name}` has value `{val}` at time `{time}`");
+        let val = state_signal.query_val_on_tmln(&time, &vcd).unwrap();
+        if let Some((_, val)) = val.current {
+            println!("Signal `{name}` has value `{val:?}` at time `{time}`");
+        }
     }
 
     Ok(())
@@ -0,0 +1,60 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+#![no_main]
+
+use fastwave_backend::{
+    parse_vcd_from_bytes_with_options, BigUint, ParseOptions, ScopeIdx, TimestampPolicy, VCD,
+};
+use libfuzzer_sys::fuzz_target;
+
+const HEADER: &[u8] = b"$timescale 1 ns $end\n\
+    $scope module top $end\n\
+    $var wire 1 ! clk $end\n\
+    $var wire 8 \" data [7:0] $end\n\
+    $var real 64 # level $end\n\
+    $var string 1 $ state $end\n\
+    $var event 1 % done $end\n\
+    $var port [3:0] <0 pins $end\n\
+    $var wire 1 & bit [0] $end\n\
+    $var wire 1 ' bit [1] $end\n\
+    $upscope $end\n\
+    $enddefinitions $end\n";
+
+fn query_scope(vcd: &VCD, scope: ScopeIdx) {
+    for signal in vcd.get_children_signal_idxs(scope) {
+        let signal = vcd.signal_from_signal_idx(signal);
+        for time in [0u32, 1, 10, 100, 1000] {
+            let _ = signal.query_val_on_tmln(&BigUint::from(time), vcd);
+        }
+    }
+    for child in vcd.child_scopes_by_idx(scope) {
+        query_scope(vcd, child);
+    }
+}
+
+// The input is used as the value change section.
+fuzz_target!(|data: &[u8]| {
+    let mut vcd = HEADER.to_vec();
+    vcd.extend_from_slice(data);
+
+    for (lenient, threads, timestamp_policy) in [
+        (false, 1, TimestampPolicy::Reject),
+        (true, 1, TimestampPolicy::Sort),
+        (true, 2, TimestampPolicy::Drop),
+    ] {
+        let options = ParseOptions {
+            lenient,
+            threads,
+            timestamp_policy,
+            assemble_buses: true,
+            ..ParseOptions::default()
+        };
+        if let Ok(vcd) = parse_vcd_from_bytes_with_options(&vcd, &options) {
+            for scope in vcd.root_scopes_by_idx() {
+                query_scope(&vcd, scope);
+            }
+        }
+    }
+});
@@ -0,0 +1,13 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+#![no_main]
+
+use fastwave_backend::{parse_vcd_header, validate_vcd};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_vcd_header(data);
+    let _ = validate_vcd(data);
+});
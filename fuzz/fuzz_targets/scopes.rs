@@ -0,0 +1,25 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+#![no_main]
+
+use fastwave_backend::{parse_vcd_from_bytes_with_options, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+// The input is used as the scope and variable declarations of a header.
+fuzz_target!(|data: &[u8]| {
+    let mut vcd = b"$timescale 1 ns $end\n".to_vec();
+    vcd.extend_from_slice(data);
+    vcd.extend_from_slice(b"\n$enddefinitions $end\n#0\n");
+
+    for lenient in [false, true] {
+        let options = ParseOptions {
+            lenient,
+            assemble_buses: true,
+            split_hierarchical_names: true,
+            ..ParseOptions::default()
+        };
+        let _ = parse_vcd_from_bytes_with_options(&vcd, &options);
+    }
+});
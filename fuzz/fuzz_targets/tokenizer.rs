@@ -0,0 +1,18 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+#![no_main]
+
+use fastwave_backend::{parse_vcd, parse_vcd_from_bytes};
+use libfuzzer_sys::fuzz_target;
+
+// The stream and the in memory tokenizers must never panic, and must agree
+// on every input.
+fuzz_target!(|data: &[u8]| {
+    let streamed = parse_vcd(data).map(|_| ()).map_err(|error| error.to_string());
+    let in_memory = parse_vcd_from_bytes(data)
+        .map(|_| ())
+        .map_err(|error| error.to_string());
+    assert_eq!(streamed, in_memory);
+});
@@ -4,9 +4,21 @@
 // the root of the folder containing the sources for this program.
 
 mod vcd;
-pub use vcd::parse::parse_vcd;
-pub use vcd::signal::{Signal, SignalType, SignalValue, SignalErrors};
-pub use vcd::types::{Metadata, Timescale, Version};
-pub use vcd::types::{ScopeIdx, SignalIdx, VCD};
+pub use vcd::error::{Cursor, Diagnostic, ParseError, Severity};
+pub use vcd::parse::parse_vcd_from_path_lazily;
+pub use vcd::parse::{parse_vcd, parse_vcd_from_bytes, parse_vcd_from_path};
+pub use vcd::parse::{
+    parse_vcd_from_bytes_with_options, parse_vcd_from_path_with_options, parse_vcd_with_options,
+    ParseOptions, SignalFilter, TimestampPolicy,
+};
+pub use vcd::parse::{parse_vcd_header, parse_vcd_header_from_path, VCDHeader};
+pub use vcd::parse::{tail_vcd_from_path, TailingVCD};
+pub use vcd::parse::{validate_vcd, validate_vcd_from_path};
+pub use vcd::parse::{CancellationToken, ParseProgress, ProgressCallback};
+pub use vcd::signal::{BitIndex, BitRange};
+pub use vcd::signal::{PortDirection, PortValue, Signal, SignalErrors, SignalType, SignalValue};
+pub use vcd::time::{ParseTimeError, Time, TimeScale, TimeUnit};
+pub use vcd::types::{DumpCommand, DumpOffInterval, HeaderSection, Metadata, Version};
+pub use vcd::types::{ScopeIdx, ScopeType, SignalIdx, VCD};
 
 pub use num::BigUint;
@@ -3,8 +3,10 @@
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
 
+pub(crate) mod error;
 pub(crate) mod parse;
 mod reader;
 pub(crate) mod signal;
+pub(crate) mod time;
 pub(crate) mod types;
 mod utilities;
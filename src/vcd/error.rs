@@ -0,0 +1,272 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// A position in the input. Lines and columns start at 1, columns and
+/// byte offsets count bytes rather than characters. For compressed files,
+/// byte offsets are into the decompressed dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    pub line: usize,
+    pub column: usize,
+    pub byte_offset: usize,
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Everything that can make parsing a VCD fail. Errors that stem from the
+/// input carry the ``Cursor`` of the offending word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input ended in the middle of a declaration or value change.
+    UnexpectedEof {
+        cursor: Cursor,
+    },
+    /// A keyword or word that isn't allowed where it was found, such as
+    /// an unknown `$var` type or a missing `$end`.
+    UnknownKeyword {
+        keyword: String,
+        expected: String,
+        cursor: Cursor,
+    },
+    BadTimestamp {
+        timestamp: String,
+        cursor: Cursor,
+    },
+    /// A timestamp that is earlier than one before it, or one that merely
+    /// repeats the timestamp before it when reported by ``validate_vcd``.
+    TimestampOutOfOrder {
+        timestamp: String,
+        previous: String,
+        cursor: Cursor,
+    },
+    /// A `$timescale` with a magnitude other than 1, 10 or 100, or an
+    /// unknown unit. Only ever reported as a warning, as parsing carries
+    /// on without a timescale.
+    BadTimescale {
+        timescale: String,
+        cursor: Cursor,
+    },
+    /// A `$timezero` that isn't a signed number. Only ever reported as a
+    /// warning, like ``BadTimescale``.
+    BadTimezero {
+        timezero: String,
+        cursor: Cursor,
+    },
+    /// The bit width of a `$var` isn't a number.
+    BadWidth {
+        width: String,
+        cursor: Cursor,
+    },
+    /// A value change that isn't made of valid value characters, or a
+    /// vector value for a real signal.
+    BadValue {
+        value: String,
+        cursor: Cursor,
+    },
+    BadDate {
+        date: String,
+        cursor: Cursor,
+    },
+    /// A value change refers to an identifier no `$var` declared.
+    UndeclaredIdentifier {
+        identifier: String,
+        cursor: Cursor,
+    },
+    /// A value change is wider than its signal. Such a signal is marked as
+    /// malformed rather than failing the whole parse, and keeps this error
+    /// as its message.
+    WidthMismatch {
+        signal: String,
+        expected: u32,
+        found: u32,
+        cursor: Cursor,
+    },
+    /// A value change for a signal whose type requires a bit width, but
+    /// which was declared without one.
+    MissingWidth {
+        signal: String,
+        cursor: Cursor,
+    },
+    /// The input is valid, but exceeds one of the parser's internal limits,
+    /// such as the number of bits a signal may have.
+    LimitExceeded {
+        limit: String,
+        cursor: Option<Cursor>,
+    },
+    /// The file is compressed with a format that this build of the crate
+    /// can't decompress.
+    UnsupportedCompression {
+        format: &'static str,
+    },
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+    /// The parse was aborted through a ``CancellationToken``.
+    Cancelled,
+    /// An invariant of the parser was broken. This is a bug in the parser
+    /// rather than a problem with the input.
+    Internal {
+        message: String,
+    },
+}
+
+impl ParseError {
+    /// The location of the offending input, if the error has one.
+    pub fn cursor(&self) -> Option<Cursor> {
+        match self {
+            ParseError::UnexpectedEof { cursor }
+            | ParseError::UnknownKeyword { cursor, .. }
+            | ParseError::BadTimestamp { cursor, .. }
+            | ParseError::TimestampOutOfOrder { cursor, .. }
+            | ParseError::BadTimescale { cursor, .. }
+            | ParseError::BadTimezero { cursor, .. }
+            | ParseError::BadWidth { cursor, .. }
+            | ParseError::BadValue { cursor, .. }
+            | ParseError::BadDate { cursor, .. }
+            | ParseError::UndeclaredIdentifier { cursor, .. }
+            | ParseError::WidthMismatch { cursor, .. }
+            | ParseError::MissingWidth { cursor, .. } => Some(*cursor),
+            ParseError::LimitExceeded { cursor, .. } => *cursor,
+            ParseError::UnsupportedCompression { .. }
+            | ParseError::Io { .. }
+            | ParseError::Cancelled
+            | ParseError::Internal { .. } => None,
+        }
+    }
+
+    pub(super) fn io(context: impl fmt::Display, error: io::Error) -> ParseError {
+        ParseError::Io {
+            kind: error.kind(),
+            message: format!("{context}: {error}"),
+        }
+    }
+
+    pub(super) fn limit(limit: impl Into<String>, cursor: Option<Cursor>) -> ParseError {
+        ParseError::LimitExceeded {
+            limit: limit.into(),
+            cursor,
+        }
+    }
+
+    pub(super) fn internal(message: impl Into<String>) -> ParseError {
+        ParseError::Internal {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.cursor() {
+            Some(cursor) => write!(f, "{} at {cursor}", Description(self)),
+            None => write!(f, "{}", Description(self)),
+        }
+    }
+}
+
+/// Formats what went wrong, leaving out where.
+struct Description<'a>(&'a ParseError);
+
+impl fmt::Display for Description<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ParseError::UnexpectedEof { .. } => f.write_str("unexpected end of file"),
+            ParseError::UnknownKeyword {
+                keyword, expected, ..
+            } => write!(f, "found `{keyword}` but expected {expected}"),
+            ParseError::BadTimestamp { timestamp, .. } => {
+                write!(f, "invalid timestamp `{timestamp}`")
+            }
+            ParseError::TimestampOutOfOrder {
+                timestamp,
+                previous,
+                ..
+            } => write!(f, "timestamp `{timestamp}` doesn't come after `{previous}`"),
+            ParseError::BadTimescale { timescale, .. } => {
+                write!(f, "invalid timescale `{timescale}`")
+            }
+            ParseError::BadTimezero { timezero, .. } => {
+                write!(f, "invalid timezero `{timezero}`")
+            }
+            ParseError::BadWidth { width, .. } => write!(f, "invalid bit width `{width}`"),
+            ParseError::BadValue { value, .. } => write!(f, "invalid value `{value}`"),
+            ParseError::BadDate { date, .. } => write!(f, "invalid date `{date}`"),
+            ParseError::UndeclaredIdentifier { identifier, .. } => {
+                write!(f, "undeclared identifier `{identifier}`")
+            }
+            ParseError::WidthMismatch {
+                signal,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "value of width {found} for signal {signal} of width {expected}"
+            ),
+            ParseError::MissingWidth { signal, .. } => write!(
+                f,
+                "value change for signal {signal}, which was declared without a width"
+            ),
+            ParseError::LimitExceeded { limit, .. } => f.write_str(limit),
+            ParseError::UnsupportedCompression { format } => write!(
+                f,
+                "the file is {format} compressed, but fastwave_backend was \
+                 built without the `{format}` feature"
+            ),
+            ParseError::Io { message, .. } => f.write_str(message),
+            ParseError::Cancelled => f.write_str("the parse was cancelled"),
+            ParseError::Internal { message } => write!(f, "internal parser error: {message}"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The offending construct was repaired, and the ``VCD`` holds our best
+    /// guess at what was meant.
+    Warning,
+    /// The offending construct was skipped.
+    Error,
+}
+
+/// A problem that a lenient parse recovered from. See
+/// ``ParseOptions::lenient``.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: ParseError,
+}
+
+impl Diagnostic {
+    /// Where in the input the problem was found, if anywhere in particular.
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.error.cursor()
+    }
+
+    /// What the problem is, without its location.
+    pub fn message(&self) -> String {
+        Description(&self.error).to_string()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}", self.error)
+    }
+}
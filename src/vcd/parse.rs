@@ -3,20 +3,179 @@
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
 
+mod buses;
 mod combinator_atoms;
+mod compression;
 mod events;
+mod filter;
+mod header;
+mod hierarchy;
+pub(super) mod lazy;
 mod metadata;
+mod options;
+mod parallel;
+mod progress;
+mod recovery;
 mod scopes;
+mod tail;
 mod types;
+mod validate;
 
-pub fn parse_vcd(file: impl std::io::Read) -> Result<super::types::VCD, String> {
-    let mut word_gen = super::reader::WordReader::new(file);
+pub use filter::SignalFilter;
+pub use header::{parse_vcd_header, parse_vcd_header_from_path, VCDHeader};
+pub use lazy::parse_vcd_from_path_lazily;
+pub use options::{ParseOptions, TimestampPolicy};
+pub use progress::{CancellationToken, ParseProgress, ProgressCallback};
+pub use tail::{tail_vcd_from_path, TailingVCD};
+pub use validate::{validate_vcd, validate_vcd_from_path};
 
-    let header = metadata::parse_metadata(&mut word_gen)?;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+use super::error::{ParseError, Severity};
+use super::reader::{SliceWordReader, WordReader, WordSource};
+use super::types::SignalIdx;
+use progress::ProgressMonitor;
+
+/// Parses a VCD from anything implementing ``std::io::Read``, such as a
+/// pipe or socket. Prefer ``parse_vcd_from_path`` or ``parse_vcd_from_bytes``
+/// when the whole dump is available, as they avoid copying the input.
+pub fn parse_vcd(file: impl std::io::Read) -> Result<super::types::VCD, ParseError> {
+    parse_vcd_with_options(file, &ParseOptions::default())
+}
+
+/// Like ``parse_vcd``, but reports progress and can be cancelled. Streams
+/// are always parsed on the calling thread.
+pub fn parse_vcd_with_options(
+    file: impl std::io::Read,
+    options: &ParseOptions,
+) -> Result<super::types::VCD, ParseError> {
+    let mut word_gen = WordReader::new(file);
+    let monitor = ProgressMonitor::new(options, None);
+    let (mut vcd, signal_map) = parse_header(&mut word_gen, &monitor, options.lenient)?;
+    rework_signals(&mut vcd, options);
+
+    let mut state = events::EventParserState::new(options.lenient, options.timestamp_policy);
+    events::parse_events(&mut word_gen, &mut vcd, &signal_map, &mut state, &monitor)?;
+    events::finish_events(&mut vcd, &state);
+
+    Ok(vcd)
+}
+
+/// Parses a VCD that is already in memory. Words are tokenized in place
+/// and borrowed straight out of ``bytes``.
+pub fn parse_vcd_from_bytes(bytes: &[u8]) -> Result<super::types::VCD, ParseError> {
+    parse_vcd_from_bytes_with_options(bytes, &ParseOptions::default())
+}
+
+/// Like ``parse_vcd_from_bytes``, but lets the value change section be
+/// parsed on several threads, reports progress and can be cancelled.
+pub fn parse_vcd_from_bytes_with_options(
+    bytes: &[u8],
+    options: &ParseOptions,
+) -> Result<super::types::VCD, ParseError> {
+    let mut word_gen = SliceWordReader::new(bytes);
+    let monitor = ProgressMonitor::new(options, Some(bytes.len()));
+    let (mut vcd, signal_map) = parse_header(&mut word_gen, &monitor, options.lenient)?;
+    rework_signals(&mut vcd, options);
+
+    let mut state = events::EventParserState::new(options.lenient, options.timestamp_policy);
+    let threads = options.thread_count();
+    if threads > 1 {
+        parallel::parse_events_parallel(
+            &word_gen,
+            &mut vcd,
+            &signal_map,
+            threads,
+            &state,
+            &monitor,
+        )?;
+    } else {
+        events::parse_events(&mut word_gen, &mut vcd, &signal_map, &mut state, &monitor)?;
+        events::finish_events(&mut vcd, &state);
+    }
+
+    Ok(vcd)
+}
+
+/// Memory maps the file at ``path`` and parses it without copying it
+/// into user space buffers. Files compressed with gzip, zstd or bzip2 are
+/// recognized by their magic bytes and decompressed on the fly, provided
+/// the matching cargo feature is enabled.
+pub fn parse_vcd_from_path(path: impl AsRef<Path>) -> Result<super::types::VCD, ParseError> {
+    parse_vcd_from_path_with_options(path, &ParseOptions::default())
+}
+
+/// Like ``parse_vcd_from_path``, but lets the value change section be
+/// parsed on several threads, reports progress and can be cancelled.
+pub fn parse_vcd_from_path_with_options(
+    path: impl AsRef<Path>,
+    options: &ParseOptions,
+) -> Result<super::types::VCD, ParseError> {
+    let path = path.as_ref();
+    let mut file =
+        File::open(path).map_err(|e| ParseError::io(format_args!("failed to open {path:?}"), e))?;
+
+    // compressed files can't be mapped and tokenized in place, so we
+    // decompress them on the fly into the streaming reader instead
+    if let Some(compression) = compression::detect_compression(&mut file)? {
+        let decompressed = compression::decompress(file, compression)?;
+        return parse_vcd_with_options(decompressed, options);
+    }
+
+    // mapping an empty file fails on some platforms
+    let len = file
+        .metadata()
+        .map_err(|e| ParseError::io(format_args!("failed to stat {path:?}"), e))?
+        .len();
+    if len == 0 {
+        return parse_vcd_from_bytes_with_options(&[], options);
+    }
+
+    // SAFETY: the mapping is only read for the duration of this function.
+    // Like every other mmap based reader, we cannot guard against another
+    // process truncating the file while we parse it.
+    let mmap = unsafe { memmap2::Mmap::map(&file) }
+        .map_err(|e| ParseError::io(format_args!("failed to map {path:?}"), e))?;
+
+    parse_vcd_from_bytes_with_options(&mmap, options)
+}
+
+/// Applies the options that rework the signals declared in the header, in
+/// the order they depend on each other: hierarchical names are split before
+/// buses are assembled within the resulting scopes, and the signal filter
+/// sees the buses.
+fn rework_signals(vcd: &mut super::types::VCD, options: &ParseOptions) {
+    if options.split_hierarchical_names {
+        hierarchy::split_hierarchical_names(vcd);
+    }
+    if options.assemble_buses {
+        buses::assemble_buses(vcd);
+    }
+    if let Some(filter) = &options.signal_filter {
+        filter::apply_signal_filter(vcd, filter);
+    }
+}
+
+/// Parses everything up to and including `$enddefinitions $end`, leaving
+/// ``word_gen`` at the start of the value change section. When ``lenient``,
+/// a header that is cut short yields the scopes and signals declared so
+/// far.
+fn parse_header<W: WordSource>(
+    word_gen: &mut W,
+    monitor: &ProgressMonitor,
+    lenient: bool,
+) -> Result<(super::types::VCD, HashMap<String, SignalIdx>), ParseError> {
+    let mut diagnostics = vec![];
+    let header = metadata::parse_metadata(word_gen, &mut diagnostics)?;
+    if !lenient {
+        diagnostics.clear();
+    }
 
     // later, we'll need to map parsed ascii symbols to their
     // respective signal indexes
-    let mut signal_map = std::collections::HashMap::new();
+    let mut signal_map = HashMap::new();
 
     // after we parse metadata, we form the VCD object
     let mut vcd = super::types::VCD {
@@ -26,10 +185,21 @@ pub fn parse_vcd(file: impl std::io::Read) -> Result<super::types::VCD, String>
         all_scopes: vec![],
         root_scopes: vec![],
         largest_timestamp: None,
+        dump_commands: vec![],
+        comments: vec![],
+        initial_values: vec![],
+        diagnostics,
+        lazy: None,
     };
 
-    scopes::parse_scopes(&mut word_gen, &mut vcd, &mut signal_map)?;
-    events::parse_events(&mut word_gen, &mut vcd, &mut signal_map)?;
+    let mut recovery = recovery::Recovery::new(lenient);
+    match scopes::parse_scopes(word_gen, &mut vcd, &mut signal_map, &mut recovery) {
+        Err(error @ ParseError::UnexpectedEof { .. }) => {
+            recovery.recover(&mut vcd, Severity::Warning, error)?
+        }
+        result => result?,
+    }
+    monitor.report(word_gen.bytes_consumed(), &vcd)?;
 
-    Ok(vcd)
+    Ok((vcd, signal_map))
 }
@@ -0,0 +1,81 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::collections::{BTreeMap, HashSet};
+
+use super::super::signal::{split_index, BitIndex, BitRange, SignalEnum};
+use super::super::types::{SignalIdx, VCD};
+
+/// Groups the single bit signals of every scope that share a name, such as
+/// `data [0]` through `data [7]`, into a bus that is added to the scope
+/// as `data [7:0]`. The bits are only grouped when their indices are
+/// contiguous, and no other signal of the scope is named like the bus.
+pub(super) fn assemble_buses(vcd: &mut VCD) {
+    for scope_idx in 0..vcd.all_scopes.len() {
+        // the bits of every base name, and the base names of signals that
+        // aren't single bits
+        let mut candidates = BTreeMap::<String, Vec<(i64, SignalIdx)>>::new();
+        let mut taken = HashSet::<String>::new();
+
+        for signal_idx in &vcd.all_scopes[scope_idx].child_signals {
+            let signal = vcd.signal_from_signal_idx(*signal_idx);
+            let num_bits = vcd.signal_from_signal_idx(signal.real_idx()).num_bits();
+            let name = signal.name();
+            let (base_name, bit_index) = match signal.index() {
+                Some(_) => (name.as_str(), signal.bit_index()),
+                None => match split_index(&name) {
+                    Some((base_name, bit_index)) => (base_name, Some(bit_index)),
+                    None => (name.as_str(), None),
+                },
+            };
+            match (bit_index, num_bits) {
+                (Some(BitIndex::Bit(bit)), Some(1)) => candidates
+                    .entry(base_name.to_string())
+                    .or_default()
+                    .push((bit, *signal_idx)),
+                _ => {
+                    taken.insert(base_name.to_string());
+                }
+            }
+        }
+
+        for (base_name, mut bits) in candidates {
+            if bits.len() < 2 || taken.contains(&base_name) {
+                continue;
+            }
+            bits.sort_by_key(|(bit, _)| std::cmp::Reverse(*bit));
+            let contiguous = bits
+                .windows(2)
+                .all(|pair| pair[1].0.checked_add(1) == Some(pair[0].0));
+            if !contiguous {
+                continue;
+            }
+
+            let range = BitRange {
+                msb: bits[0].0,
+                lsb: bits[bits.len() - 1].0,
+            };
+            let first_bit = vcd.signal_from_signal_idx(bits[0].1);
+            let mut path = first_bit.path().to_vec();
+            path.pop();
+            path.push(base_name.clone());
+            // aliases have no type of their own, so take the aliased one's
+            let real_first_bit = vcd.signal_from_signal_idx(first_bit.real_idx());
+            let Some(signal_type) = real_first_bit.signal_type().cloned() else {
+                continue;
+            };
+
+            let self_idx = SignalIdx(vcd.all_signals.len());
+            vcd.all_signals.push(SignalEnum::Bus {
+                name: base_name,
+                path,
+                signal_type,
+                range,
+                bits: bits.into_iter().map(|(_, signal_idx)| signal_idx).collect(),
+                self_idx,
+            });
+            vcd.all_scopes[scope_idx].child_signals.push(self_idx);
+        }
+    }
+}
@@ -2,7 +2,8 @@
 // This program is distributed under both the GPLV3 license
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
-use super::super::reader::{next_word, WordReader};
+use super::super::error::ParseError;
+use super::super::reader::{next_word, WordSource};
 use super::types::ParseResult;
 
 pub(super) fn digit(chr: u8) -> bool {
@@ -12,23 +13,6 @@ pub(super) fn digit(chr: u8) -> bool {
     (chr >= zero) && (nine >= chr)
 }
 
-pub(super) fn take_until(word: &str, pattern: u8) -> ParseResult<'_> {
-    let mut new_start = 0;
-
-    for chr in word.as_bytes() {
-        if *chr == pattern {
-            break;
-        } else {
-            new_start += 1;
-        }
-    }
-
-    ParseResult {
-        matched: &word[0..new_start],
-        residual: &word[new_start..],
-    }
-}
-
 pub(super) fn take_while(word: &str, cond: fn(u8) -> bool) -> ParseResult<'_> {
     let mut new_start = 0;
 
@@ -67,17 +51,17 @@ pub(super) fn tag<'a>(word: &'a str, pattern: &'a str) -> ParseResult<'a> {
     }
 }
 
-pub(super) fn ident<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
-    keyword: &str,
-) -> Result<(), String> {
+pub(super) fn ident<W: WordSource>(word_reader: &mut W, keyword: &str) -> Result<(), ParseError> {
     // let keyword = "module";
     let (word, cursor) = next_word!(word_reader)?;
 
     if word == keyword {
         Ok(())
     } else {
-        let err = format!("found keyword `{word}` but expected `{keyword}` on {cursor:?}");
-        Err(err)
+        Err(ParseError::UnknownKeyword {
+            keyword: word.to_string(),
+            expected: format!("`{keyword}`"),
+            cursor,
+        })
     }
 }
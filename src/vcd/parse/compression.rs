@@ -0,0 +1,73 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use super::super::error::ParseError;
+
+/// The compression formats we know how to recognize from the first few
+/// bytes of a file. Whether we can actually decompress them depends on
+/// which cargo features are enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    fn from_magic(magic: &[u8]) -> Option<Compression> {
+        match magic {
+            [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+            [b'B', b'Z', b'h', ..] => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+}
+
+/// Peeks at the first few bytes of ``file`` to see whether it is
+/// compressed, leaving the file positioned at its start either way.
+pub(super) fn detect_compression(file: &mut File) -> Result<Option<Compression>, ParseError> {
+    let mut magic = Vec::with_capacity(4);
+    file.by_ref()
+        .take(4)
+        .read_to_end(&mut magic)
+        .map_err(|e| ParseError::io("failed to read the file's magic bytes", e))?;
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| ParseError::io("failed to rewind the file", e))?;
+
+    Ok(Compression::from_magic(&magic))
+}
+
+/// Wraps ``file`` in a decoder for ``compression`` so that it can be
+/// streamed straight into a ``WordReader``.
+pub(super) fn decompress(
+    file: File,
+    compression: Compression,
+) -> Result<Box<dyn Read>, ParseError> {
+    match compression {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(file))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let decoder = zstd::stream::read::Decoder::new(file)
+                .map_err(|e| ParseError::io("failed to start decompressing", e))?;
+            Ok(Box::new(decoder))
+        }
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(file))),
+        #[allow(unreachable_patterns)]
+        _ => {
+            let _ = file;
+            let format = match compression {
+                Compression::Gzip => "gzip",
+                Compression::Zstd => "zstd",
+                Compression::Bzip2 => "bzip2",
+            };
+            Err(ParseError::UnsupportedCompression { format })
+        }
+    }
+}
@@ -4,80 +4,488 @@
 // the root of the folder containing the sources for this program.
 
 use num::BigUint;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-use super::super::reader::{next_word, Cursor, Line, Word, WordReader};
-use super::super::signal::{LsbIdxOfTmstmpValOnTmln, SignalEnum};
-use super::super::types::{SignalIdx, VCD};
+use super::super::error::{Cursor, ParseError, Severity};
+use super::super::reader::{next_word, ReaderPosition, SliceWordReader, WordSource};
+use super::super::signal::{LsbIdxOfTmstmpValOnTmln, PortValue, SignalEnum, SignalValue};
+use super::super::types::{DumpCommand, SignalIdx, VCD};
 use super::super::utilities::{binary_str_to_vec_u8, BinaryParserErrTypes};
+use super::lazy::{Block, EventIndex};
+use super::options::TimestampPolicy;
+use super::progress::{ProgressMonitor, REPORT_INTERVAL};
+use super::recovery::Recovery;
 
-pub(super) fn parse_events<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
+/// Everything ``parse_events`` needs to remember between two words. Kept
+/// outside of ``parse_events`` so that parsing can be resumed where it
+/// left off once more of a file becomes available.
+#[derive(Debug, Default, Clone)]
+pub(super) struct EventParserState {
+    /// The timestamp on the timeline that value changes are currently
+    /// being recorded against.
+    curr_tmstmp_lsb_idx: u32,
+    curr_tmstmp_len_u8: u8,
+    /// The value of that timestamp, which is the largest one seen so far
+    /// unless blocks are being sorted.
+    pub(super) curr_tmstmp: Option<BigUint>,
+    pub(super) timestamp_policy: TimestampPolicy,
+    /// Whether the value changes of the current block are being dropped,
+    /// as its timestamp is out of order.
+    dropping: bool,
+    /// Whether a block was recorded out of order, so that the value
+    /// changes of every signal have to be sorted.
+    pub(super) out_of_order: bool,
+    /// The text of the `$comment` we are in, if any, up to the current word.
+    comment: Option<String>,
+    /// The simulation keyword whose `$end` we have yet to see, if any.
+    open_keyword: Option<String>,
+    /// When replaying a block of a lazily parsed VCD, the length the
+    /// timeline had at this point of the first pass. Timestamps are then
+    /// counted against the timeline the first pass built instead of being
+    /// appended to it.
+    replayed_timeline_len: Option<usize>,
+    /// When set, the blocks that ``parse_vcd_from_path_lazily`` decodes
+    /// signals from are recorded here.
+    pub(super) index: Option<EventIndex>,
+    recovery: Recovery,
+}
+
+impl EventParserState {
+    /// A fresh state that fails on the first malformed value change unless
+    /// ``lenient``.
+    pub(super) fn new(lenient: bool, timestamp_policy: TimestampPolicy) -> Self {
+        EventParserState {
+            recovery: Recovery::new(lenient),
+            timestamp_policy,
+            ..Default::default()
+        }
+    }
+
+    /// A fresh state for ``validate_vcd``, which reports every problem
+    /// found along with what parsing would otherwise tolerate.
+    pub(super) fn validating() -> Self {
+        EventParserState {
+            recovery: Recovery::pedantic(),
+            timestamp_policy: TimestampPolicy::Reject,
+            ..Default::default()
+        }
+    }
+
+    /// A fresh state that indexes the value changes of the signals of
+    /// ``vcd``, whose value change section starts at ``position``.
+    pub(super) fn indexing(
+        lenient: bool,
+        timestamp_policy: TimestampPolicy,
+        vcd: &VCD,
+        position: ReaderPosition,
+    ) -> Self {
+        let state = EventParserState::new(lenient, timestamp_policy);
+        let first_block = state.replayable(vcd, position);
+        EventParserState {
+            index: Some(EventIndex::new(first_block, vcd.all_signals.len())),
+            ..state
+        }
+    }
+
+    /// The state to replay the value changes from ``position`` on with, as
+    /// things stand in ``vcd``. Problems are reported anew, and only ever to
+    /// the replayed VCD.
+    fn replayable(&self, vcd: &VCD, position: ReaderPosition) -> Block {
+        let state = EventParserState {
+            curr_tmstmp_lsb_idx: self.curr_tmstmp_lsb_idx,
+            curr_tmstmp_len_u8: self.curr_tmstmp_len_u8,
+            curr_tmstmp: self.curr_tmstmp.clone(),
+            timestamp_policy: self.timestamp_policy,
+            dropping: self.dropping,
+            out_of_order: false,
+            comment: self.comment.clone(),
+            open_keyword: self.open_keyword.clone(),
+            replayed_timeline_len: Some(vcd.tmstmps_encoded_as_u8s.len()),
+            index: None,
+            recovery: Recovery::new(self.recovery.is_lenient()),
+        };
+        Block {
+            position,
+            state,
+            largest_timestamp: vcd.largest_timestamp.clone(),
+        }
+    }
+
+    /// Starts a new block of the index at the timestamp word at ``cursor``,
+    /// once the current block has grown long enough.
+    fn index_timestamp(&mut self, vcd: &VCD, cursor: Cursor) {
+        let starts_block = self
+            .index
+            .as_ref()
+            .is_some_and(|index| index.is_due(cursor.byte_offset));
+        if starts_block {
+            let position = ReaderPosition {
+                offset: cursor.byte_offset,
+                line: cursor.line,
+                line_start: cursor.byte_offset + 1 - cursor.column,
+            };
+            let block = self.replayable(vcd, position);
+            if let Some(index) = &mut self.index {
+                index.push_block(block);
+            }
+        }
+    }
+
+    fn in_dumpvars(&self) -> bool {
+        self.open_keyword.as_deref().and_then(dump_command) == Some(DumpCommand::DumpVars)
+    }
+}
+
+/// Extends a binary value shorter than its signal to ``num_bits`` bits, as
+/// IEEE 1364 asks: with `x` or `z` when that is the leftmost bit, and with
+/// `0` otherwise. The other unknown states GHDL writes, `u`, `w` and `-`,
+/// are extended like `x` is.
+fn left_extend(value: &str, num_bits: u32) -> String {
+    let Some(missing) = usize::try_from(num_bits)
+        .ok()
+        .and_then(|num_bits| num_bits.checked_sub(value.len()))
+        .filter(|missing| *missing > 0)
+    else {
+        return value.to_string();
+    };
+    let fill = match value.as_bytes().first() {
+        Some(leftmost @ (b'x' | b'z' | b'u' | b'w' | b'-')) => char::from(*leftmost),
+        _ => '0',
+    };
+    std::iter::repeat_n(fill, missing)
+        .chain(value.chars())
+        .collect()
+}
+
+/// The number of words that follow the first word of a value change
+/// starting with ``first_letter``: the identifier of vectors, reals and
+/// strings, and the strengths and identifier of ports.
+fn trailing_words(first_letter: &str) -> usize {
+    match first_letter {
+        "b" | "B" | "s" | "S" | "r" | "R" => 1,
+        "p" => 3,
+        _ => 0,
+    }
+}
+
+/// The offset just past the last value change that ``word_reader`` holds
+/// in full, were ``parse_events`` to pick up from ``state``. Everything
+/// after it may still be missing words, such as the identifier of a
+/// vector that has yet to be written.
+pub(super) fn complete_events_end(
+    mut word_reader: SliceWordReader,
+    state: &EventParserState,
+) -> usize {
+    let mut in_comment = state.comment.is_some();
+    let mut end = word_reader.position().offset;
+    'words: while let Some((word, _)) = word_reader.next_word() {
+        if in_comment || word == "$comment" {
+            in_comment = word != "$end";
+        } else {
+            for _ in 0..trailing_words(word.get(0..1).unwrap_or_default()) {
+                if word_reader.next_word().is_none() {
+                    break 'words;
+                }
+            }
+        }
+        end = word_reader.position().offset;
+    }
+    end
+}
+
+/// The data signal that a value change of ``signal_idx`` changes, which is
+/// noted in the index of ``state``, if it keeps one.
+fn changing_signal<'a>(
+    vcd: &'a mut VCD,
+    state: &mut EventParserState,
+    signal_idx: &SignalIdx,
+) -> Result<&'a mut SignalEnum, ParseError> {
+    let signal = vcd.dealiasing_signal_idx_to_signal_lookup_mut(signal_idx)?;
+    if let (Some(index), SignalEnum::Data { self_idx, .. }) = (&mut state.index, &*signal) {
+        index.note_change(*self_idx);
+    }
+    Ok(signal)
+}
+
+fn dump_command(word: &str) -> Option<DumpCommand> {
+    match word {
+        // extended VCDs have a keyword of their own for each
+        "$dumpvars" | "$dumpports" => Some(DumpCommand::DumpVars),
+        "$dumpall" | "$dumpportsall" => Some(DumpCommand::DumpAll),
+        "$dumpon" | "$dumpportson" => Some(DumpCommand::DumpOn),
+        "$dumpoff" | "$dumpportsoff" => Some(DumpCommand::DumpOff),
+        _ => None,
+    }
+}
+
+fn bad_value(word: &str, cursor: Cursor) -> ParseError {
+    ParseError::BadValue {
+        value: word.to_string(),
+        cursor,
+    }
+}
+
+/// The error for ``word`` appearing before the `$end` of ``open_keyword``.
+fn unclosed(word: &str, open_keyword: &str, cursor: Cursor) -> ParseError {
+    ParseError::UnknownKeyword {
+        keyword: word.to_string(),
+        expected: format!("`$end` to close `{open_keyword}`"),
+        cursor,
+    }
+}
+
+/// Sorts the value changes of every signal if ``state`` recorded blocks
+/// out of order. To be called once the whole dump has been parsed.
+pub(super) fn finish_events(vcd: &mut VCD, state: &EventParserState) {
+    if state.out_of_order {
+        sort_value_changes(vcd);
+    }
+}
+
+/// Puts the value changes of every signal in chronological order.
+pub(super) fn sort_value_changes(vcd: &mut VCD) {
+    for signal in &mut vcd.all_signals {
+        signal.sort_value_changes(&vcd.tmstmps_encoded_as_u8s);
+    }
+    vcd.dump_commands.sort_by(|(_, a), (_, b)| a.cmp(b));
+    vcd.comments.sort_by(|(_, a), (_, b)| a.cmp(b));
+}
+
+pub(super) fn parse_events<W: WordSource>(
+    word_reader: &mut W,
     vcd: &mut VCD,
-    signal_map: &mut HashMap<String, SignalIdx>,
-) -> Result<(), String> {
-    let mut curr_tmstmp_lsb_idx = 0u32;
-    let mut curr_tmstmp_len_u8 = 0u8;
+    signal_map: &HashMap<String, SignalIdx>,
+    state: &mut EventParserState,
+    monitor: &ProgressMonitor,
+) -> Result<(), ParseError> {
+    let mut last_reported = word_reader.bytes_consumed();
     loop {
+        let bytes_consumed = word_reader.bytes_consumed();
+        if bytes_consumed - last_reported >= REPORT_INTERVAL {
+            monitor.report(bytes_consumed - last_reported, vcd)?;
+            last_reported = bytes_consumed;
+        }
+
         let next_word = word_reader.next_word();
 
         // The following is the only case where eof is not an error.
         // If we've reached the end of the file, then there is obviously
         // nothing left to do...
-        if next_word.is_none() {
+        let Some((word, cursor)) = next_word else {
+            if let Some(error) = word_reader.take_error() {
+                return Err(error);
+            }
+            monitor.report(word_reader.bytes_consumed() - last_reported, vcd)?;
+            if state.comment.is_some() || state.open_keyword.is_some() {
+                let cursor = word_reader.cursor();
+                state
+                    .recovery
+                    .nitpick(vcd, Severity::Error, || ParseError::UnexpectedEof {
+                        cursor,
+                    });
+            }
             break;
         };
 
-        let (word, cursor) = next_word.unwrap();
-        let Cursor(Line(_), Word(word_in_line_idx)) = cursor;
-        // we only want to match on the first word in a line
-        if word_in_line_idx != 1 {
+        // comments may contain just about anything, including words that
+        // look like value changes
+        if let Some(comment) = &mut state.comment {
+            if word == "$end" {
+                let time = state.curr_tmstmp.clone().unwrap_or_default();
+                vcd.comments.push((std::mem::take(comment), time));
+                state.comment = None;
+            } else {
+                if !comment.is_empty() {
+                    comment.push(' ');
+                }
+                comment.push_str(word);
+            }
             continue;
         }
-        match &word[0..1] {
-            "$" => {}
+
+        // the value changes of a dropped block are skipped along with the
+        // identifiers of vectors, reals and strings, and the strengths and
+        // identifiers of ports
+        // words never start with whitespace, so they have a first letter,
+        // though it needn't be ASCII
+        let first_letter = word.get(0..1).unwrap_or_default();
+        if state.dropping && !matches!(first_letter, "$" | "#") {
+            let identifier = match trailing_words(first_letter) {
+                0 => Some((word.get(1..).unwrap_or_default(), cursor)),
+                trailing_words => {
+                    for _ in 1..trailing_words {
+                        word_reader.next_word();
+                    }
+                    word_reader.next_word()
+                }
+            };
+            // validation still checks that the identifiers were declared
+            if let Some((identifier, cursor)) = identifier {
+                if state.recovery.is_pedantic() && !signal_map.contains_key(identifier) {
+                    state.recovery.nitpick(vcd, Severity::Error, || {
+                        ParseError::UndeclaredIdentifier {
+                            identifier: identifier.to_string(),
+                            cursor,
+                        }
+                    });
+                }
+            }
+            continue;
+        }
+
+        // We work on the token stream rather than on lines, as value
+        // changes may share a line with each other, with a timestamp, or
+        // with keywords such as `$dumpvars`.
+        match first_letter {
+            // Simulation keywords like `$dumpvars` merely wrap value
+            // changes, which we pick up as regular tokens. We note where
+            // they occur, and comments are the only keyword whose contents
+            // we skip.
+            "$" if word == "$comment" => state.comment = Some(String::new()),
+            "$" if word == "$end" => {
+                if state.open_keyword.take().is_none() {
+                    state
+                        .recovery
+                        .nitpick(vcd, Severity::Error, || ParseError::UnknownKeyword {
+                            keyword: word.to_string(),
+                            expected: "a simulation keyword before it".to_string(),
+                            cursor,
+                        });
+                }
+            }
+            "$" => {
+                let command = dump_command(word);
+                if command.is_none() && word != "$vcdclose" {
+                    state
+                        .recovery
+                        .nitpick(vcd, Severity::Error, || ParseError::UnknownKeyword {
+                            keyword: word.to_string(),
+                            expected: "one of [\"$comment\", \"$dumpall\", \"$dumpoff\", \
+                                       \"$dumpon\", \"$dumpvars\"]"
+                                .to_string(),
+                            cursor,
+                        });
+                    continue;
+                }
+
+                if let Some(open_keyword) = state.open_keyword.replace(word.to_string()) {
+                    state.recovery.nitpick(vcd, Severity::Error, || {
+                        unclosed(word, &open_keyword, cursor)
+                    });
+                }
+                if let Some(command) = command {
+                    let time = state.curr_tmstmp.clone().unwrap_or_default();
+                    vcd.dump_commands.push((command, time));
+                    if let (DumpCommand::DumpVars, Some(index)) = (command, &mut state.index) {
+                        index.note_dumpvars();
+                    }
+                }
+            }
             "#" => {
+                state.index_timestamp(vcd, cursor);
+
+                // some simulators never close their `$dumpvars` block, but
+                // a block cannot span a timestamp. Extended VCDs end with
+                // `$vcdclose` and the final time of the simulation.
+                match state.open_keyword.take() {
+                    Some(open_keyword) if open_keyword == "$vcdclose" => {
+                        state.open_keyword = Some(open_keyword);
+                    }
+                    Some(open_keyword) => {
+                        state.recovery.nitpick(vcd, Severity::Warning, || {
+                            unclosed(word, &open_keyword, cursor)
+                        });
+                    }
+                    None => {}
+                }
+
                 let value = &word[1..];
-                let (f, l) = (file!(), line!());
-                let value_biguint = BigUint::parse_bytes(value.as_bytes(), 10)
-                    .ok_or(())
-                    .map_err(|_| {
-                        format!(
-                            "Error near {f}:{l}. Failed to parse {value} as BigInt at {cursor:?}"
-                        )
-                    })?;
+                let value_biguint = match BigUint::parse_bytes(value.as_bytes(), 10) {
+                    Some(value_biguint) => value_biguint,
+                    None => {
+                        // value changes that follow are recorded against
+                        // the previous timestamp
+                        let error = ParseError::BadTimestamp {
+                            timestamp: word.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
+
+                // repeated timestamps share a single entry on the timeline
+                if state.curr_tmstmp.as_ref() == Some(&value_biguint) {
+                    state.recovery.nitpick(vcd, Severity::Warning, || {
+                        ParseError::TimestampOutOfOrder {
+                            timestamp: word.to_string(),
+                            previous: format!("#{value_biguint}"),
+                            cursor,
+                        }
+                    });
+                    state.dropping = false;
+                    continue;
+                }
+
+                let order = vcd
+                    .largest_timestamp
+                    .as_ref()
+                    .map_or(Ordering::Greater, |largest| value_biguint.cmp(largest));
+                if order == Ordering::Less {
+                    match state.timestamp_policy {
+                        TimestampPolicy::Reject => {
+                            let largest = vcd.largest_timestamp.clone().unwrap_or_default();
+                            let error = ParseError::TimestampOutOfOrder {
+                                timestamp: word.to_string(),
+                                previous: format!("#{largest}"),
+                                cursor,
+                            };
+                            state.recovery.recover_once(vcd, Severity::Error, error)?;
+                            state.dropping = true;
+                            continue;
+                        }
+                        TimestampPolicy::Drop => {
+                            state.dropping = true;
+                            continue;
+                        }
+                        TimestampPolicy::Sort => state.out_of_order = true,
+                    }
+                }
+                state.dropping = false;
+
                 let mut value = value_biguint.to_bytes_le();
                 // TODO : u32 helps with less memory, but should ideally likely be
                 // configurable.
-                curr_tmstmp_len_u8 = u8::try_from(value.len()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}. Failed to convert from usize to u8.",
-                        file!(),
-                        line!()
-                    )
+                state.curr_tmstmp_len_u8 = u8::try_from(value.len())
+                    .map_err(|_| ParseError::limit("timestamp exceeds 255 bytes", Some(cursor)))?;
+                let timeline_len = state
+                    .replayed_timeline_len
+                    .unwrap_or(vcd.tmstmps_encoded_as_u8s.len());
+                state.curr_tmstmp_lsb_idx = u32::try_from(timeline_len).map_err(|_| {
+                    ParseError::limit("the timeline exceeds 2^32 - 1 bytes", Some(cursor))
                 })?;
-                curr_tmstmp_lsb_idx =
-                    u32::try_from(vcd.tmstmps_encoded_as_u8s.len()).map_err(|_| {
-                        format!(
-                            "Error near {}:{}. Failed to convert from usize to u32.",
-                            file!(),
-                            line!()
-                        )
-                    })?;
-                vcd.tmstmps_encoded_as_u8s.append(&mut value);
-                vcd.largest_timestamp = Some(value_biguint);
+                match &mut state.replayed_timeline_len {
+                    Some(timeline_len) => *timeline_len += value.len(),
+                    None => vcd.tmstmps_encoded_as_u8s.append(&mut value),
+                }
+                if order == Ordering::Greater {
+                    vcd.largest_timestamp = Some(value_biguint.clone());
+                }
+                state.curr_tmstmp = Some(value_biguint);
             }
 
             // handle the case of an n bit signal whose value must be parsed
-            "b" => {
+            "b" | "B" => {
+                let (uppercase, value_cursor) = (word.starts_with('B'), cursor);
                 let binary_value = &word[1..];
+                if binary_value.is_empty() {
+                    state
+                        .recovery
+                        .nitpick(vcd, Severity::Error, || bad_value(word, cursor));
+                }
                 let observed_num_bits = u32::try_from(binary_value.len()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}, {cursor:?}. \
-                        Found signal with more than 2^32 - 1 bits.",
-                        file!(),
-                        line!()
-                    )
+                    ParseError::limit("value has more than 2^32 - 1 bits", Some(cursor))
                 })?;
 
                 let mut value_u8: Vec<u8> = Vec::new();
@@ -101,37 +509,60 @@ pub(super) fn parse_events<R: std::io::Read>(
                         | BinaryParserErrTypes::DashValue
                         | BinaryParserErrTypes::LValue,
                     ) => {
+                        // the VHDL values of nine valued logic are common
+                        // enough that we read them, but they aren't part of
+                        // the standard
+                        if !binary_value.bytes().all(|bit| b"01xXzZ".contains(&bit)) {
+                            state
+                                .recovery
+                                .nitpick(vcd, Severity::Warning, || bad_value(word, cursor));
+                        }
                         store_as_string = true;
                         // Turn to lower case for consistency
                         value_string = binary_value.to_ascii_lowercase();
                     }
-                    Err(e) => {
-                        let (f, l) = (file!(), line!());
-                        Err(e).map_err(|e| {
-                            format!("Error near {f}:{l}. Error {e:?} at {cursor:?}.")
-                        })?;
+                    Err(_) => {
+                        let error = ParseError::BadValue {
+                            value: word.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        // skip the identifier the value was meant for
+                        word_reader.next_word();
+                        continue;
                     }
                 }
 
                 // this word should be the signal alias
-                let (word, cursor) = next_word!(word_reader)?;
+                let (word, cursor) = match next_word!(word_reader) {
+                    Ok(word) => word,
+                    Err(error) => {
+                        state.recovery.recover_once(vcd, Severity::Warning, error)?;
+                        continue;
+                    }
+                };
 
                 // lookup signal idx
-                let signal_idx = signal_map.get(word).ok_or(()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}. Failed to lookup signal {word} at {cursor:?}",
-                        file!(),
-                        line!()
-                    )
-                })?;
+                let signal_idx = match signal_map.get(word) {
+                    Some(signal_idx) => signal_idx,
+                    None => {
+                        let error = ParseError::UndeclaredIdentifier {
+                            identifier: word.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
 
-                let signal = vcd.dealiasing_signal_idx_to_signal_lookup_mut(signal_idx)?;
+                let signal = changing_signal(vcd, state, signal_idx)?;
 
-                match signal {
+                let initial_value = match signal {
                     SignalEnum::Data {
                         name,
-                        signal_type,
                         ref mut signal_error,
+                        loaded,
+                        signal_type,
                         num_bits,
                         num_bytes,
                         nums_encoded_as_fixed_width_le_u8,
@@ -143,8 +574,26 @@ pub(super) fn parse_events<R: std::io::Read>(
                         ..
                     } => {
                         // we've already identified in a prior loop iteration that the signal has
-                        // an error
-                        if signal_error.is_some() {
+                        // an error, or the signal wasn't selected to be loaded
+                        if signal_error.is_some() || !*loaded {
+                            continue;
+                        }
+
+                        // reals are kept as the bits of an f64, which a
+                        // vector can't be stored as, so only this value is
+                        // dropped when parsing leniently
+                        if signal_type.is_real() {
+                            let digits = if store_as_string {
+                                value_string
+                            } else {
+                                let width = observed_num_bits as usize;
+                                format!("{:0width$b}", BigUint::from_bytes_le(&value_u8))
+                            };
+                            let error = ParseError::BadValue {
+                                value: format!("{}{digits}", if uppercase { 'B' } else { 'b' }),
+                                cursor: value_cursor,
+                            };
+                            state.recovery.recover_once(vcd, Severity::Error, error)?;
                             continue;
                         }
 
@@ -152,61 +601,73 @@ pub(super) fn parse_events<R: std::io::Read>(
                         // and verify that it is not greater than the numbits declared
                         // when the signal was declared.
                         // Also account for the error case of a bitwidth of `None`
-                        match num_bits {
-                            Some(ref num_bits) => {
+                        let num_bits = match num_bits {
+                            Some(num_bits) => {
                                 if observed_num_bits > *num_bits {
-                                    let (f, l) = (file!(), line!());
-                                    let msg = format!("\
-                                        Error near {f}:{l}. The bitwidth for signal {name} \
-                                        of signal_type {signal_type:?} is expected to be `{num_bits}` not \
-                                        `{observed_num_bits}`. \
-                                        This error occurred while parsing the vcd file at \
-                                        {cursor:?}");
-                                    *signal_error = Some(msg);
+                                    let error = ParseError::WidthMismatch {
+                                        signal: name.clone(),
+                                        expected: *num_bits,
+                                        found: observed_num_bits,
+                                        cursor,
+                                    };
+                                    *signal_error = Some(error.to_string());
+                                    state.recovery.note_once(vcd, Severity::Error, error);
                                     continue;
                                 }
+                                *num_bits
                             }
                             None => {
-                                let (f, l) = (file!(), line!());
-                                let msg = format!(
-                                    "\
-                                    Error near {f}:{l}. The bitwidth for signal {name} \
-                                    must be specified for a signal of type {signal_type:?}. \
-                                    This error occurred while parsing the vcd file at \
-                                    {cursor:?}"
-                                );
-                                Err(msg)?;
+                                let error = ParseError::MissingWidth {
+                                    signal: name.clone(),
+                                    cursor,
+                                };
+                                // like a width mismatch, this only spoils the
+                                // one signal when parsing leniently
+                                *signal_error = Some(error.to_string());
+                                state.recovery.recover_once(vcd, Severity::Error, error)?;
+                                continue;
                             }
                         };
 
+                        // four-state values are stored at their full width,
+                        // just like numbers are
+                        if store_as_string {
+                            value_string = left_extend(&value_string, num_bits);
+                        }
+                        let initial_value = state.in_dumpvars().then(|| {
+                            if store_as_string {
+                                SignalValue::String(value_string.clone())
+                            } else {
+                                SignalValue::BigUint(BigUint::from_bytes_le(&value_u8))
+                            }
+                        });
+
                         if store_as_string {
                             lsb_indxs_of_string_tmstmp_vals_on_tmln
-                                .push(LsbIdxOfTmstmpValOnTmln(curr_tmstmp_lsb_idx));
-                            byte_len_of_string_tmstmp_vals_on_tmln.push(curr_tmstmp_len_u8);
+                                .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                            byte_len_of_string_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
                             string_vals.push(value_string);
-                            Ok(())
+                            Ok(initial_value)
                         } else {
                             // timestamp stuff
                             lsb_indxs_of_num_tmstmp_vals_on_tmln
-                                .push(LsbIdxOfTmstmpValOnTmln(curr_tmstmp_lsb_idx));
-                            byte_len_of_num_tmstmp_vals_on_tmln.push(curr_tmstmp_len_u8);
+                                .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                            byte_len_of_num_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
 
                             // value stuff
                             // we may need to zero extend values
                             // so that we end up storing all values
                             // of a particular signal in a consistent
                             // amount of bytes
-                            let bytes_required = num_bytes.ok_or_else(|| {
-                                format!("Error near {}:{}. num_bytes empty.", file!(), line!())
-                            })?;
+                            let bytes_required =
+                                num_bytes.ok_or_else(|| ParseError::internal("num_bytes empty"))?;
                             let mut curr_num_bytes =
                                 u16::try_from(value_u8.len()).map_err(|_| {
-                                    format!(
-                                        "Error near {}:{}. \
-                                     Found signal {name} with with value change of greater \
-                                     than 2^32 - 1 bits on {cursor:?}.",
-                                        file!(),
-                                        line!()
+                                    ParseError::limit(
+                                        format!(
+                                            "value change of signal {name} exceeds 65535 bytes"
+                                        ),
+                                        Some(cursor),
                                     )
                                 })?;
 
@@ -215,38 +676,44 @@ pub(super) fn parse_events<R: std::io::Read>(
                                 nums_encoded_as_fixed_width_le_u8.push(0u8);
                                 curr_num_bytes += 1;
                             }
-                            Ok(())
+                            Ok(initial_value)
                         }
                     }
-                    SignalEnum::Alias { .. } => {
-                        let (f, l) = (file!(), line!());
-                        let msg = format!(
-                            "Error near {f}:{l}, a signal alias should not point to a signal alias.\n\
-                                This error occurred while parsing vcd file at {cursor:?}");
-                        Err(msg)
-                    }
+                    SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                        "a signal alias shouldn't point to a signal alias",
+                    )),
                 }?;
+                if let Some(value) = initial_value {
+                    vcd.initial_values.push((*signal_idx, value));
+                }
             }
 
             // handle the case of a one bit signal whose value is set to `0`
             "0" => {
+                let initial_value = state
+                    .in_dumpvars()
+                    .then(|| SignalValue::BigUint(BigUint::from(0u8)));
                 // lookup signal idx
                 let hash = &word[1..];
-                let signal_idx = signal_map.get(hash).ok_or(()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}. Failed to lookup signal {hash} at {cursor:?}",
-                        file!(),
-                        line!()
-                    )
-                })?;
+                let signal_idx = match signal_map.get(hash) {
+                    Some(signal_idx) => signal_idx,
+                    None => {
+                        let error = ParseError::UndeclaredIdentifier {
+                            identifier: hash.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
 
-                let signal = vcd.dealiasing_signal_idx_to_signal_lookup_mut(signal_idx)?;
+                let signal = changing_signal(vcd, state, signal_idx)?;
 
                 match signal {
                     SignalEnum::Data {
                         name,
-                        signal_type,
                         ref mut signal_error,
+                        loaded,
                         num_bits,
                         num_bytes,
                         nums_encoded_as_fixed_width_le_u8,
@@ -254,8 +721,8 @@ pub(super) fn parse_events<R: std::io::Read>(
                         byte_len_of_num_tmstmp_vals_on_tmln,
                         ..
                     } => {
-                        // if this is a bad signal, go ahead and skip it
-                        if signal_error.is_some() {
+                        // if this is a bad or unselected signal, go ahead and skip it
+                        if signal_error.is_some() || !*loaded {
                             continue;
                         }
 
@@ -264,44 +731,41 @@ pub(super) fn parse_events<R: std::io::Read>(
                         match num_bits {
                             Some(ref num_bits) => {
                                 if *num_bits != 1 {
-                                    let (f, l) = (file!(), line!());
-                                    let msg = format!(
-                                        "\
-                                        Error near {f}:{l}. The bitwidth for signal {name} \
-                                        of signal_type {signal_type:?} is expected to be `1` not \
-                                        `{num_bits}`. \
-                                        This error occurred while parsing the vcd file at \
-                                        {cursor:?}"
-                                    );
-                                    *signal_error = Some(msg);
+                                    let error = ParseError::WidthMismatch {
+                                        signal: name.clone(),
+                                        expected: *num_bits,
+                                        found: 1,
+                                        cursor,
+                                    };
+                                    *signal_error = Some(error.to_string());
+                                    state.recovery.note_once(vcd, Severity::Error, error);
                                     continue;
                                 }
                             }
                             None => {
-                                let (f, l) = (file!(), line!());
-                                let msg = format!(
-                                    "\
-                                    Error near {f}:{l}. The bitwidth for signal {name} \
-                                    must be specified for a signal of type {signal_type:?}. \
-                                    This error occurred while parsing the vcd file at \
-                                    {cursor:?}"
-                                );
-                                Err(msg)?;
+                                let error = ParseError::MissingWidth {
+                                    signal: name.clone(),
+                                    cursor,
+                                };
+                                // like a width mismatch, this only spoils the
+                                // one signal when parsing leniently
+                                *signal_error = Some(error.to_string());
+                                state.recovery.recover_once(vcd, Severity::Error, error)?;
+                                continue;
                             }
                         };
                         // timestamp stuff
                         lsb_indxs_of_num_tmstmp_vals_on_tmln
-                            .push(LsbIdxOfTmstmpValOnTmln(curr_tmstmp_lsb_idx));
-                        byte_len_of_num_tmstmp_vals_on_tmln.push(curr_tmstmp_len_u8);
+                            .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                        byte_len_of_num_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
 
                         // value stuff
                         // we may need to zero extend values
                         // so that we end up storing all values
                         // of a particular signal in a consistent
                         // amount of bytes
-                        let bytes_required = num_bytes.ok_or_else(|| {
-                            format!("Error near {}:{}. num_bytes empty.", file!(), line!())
-                        })?;
+                        let bytes_required =
+                            num_bytes.ok_or_else(|| ParseError::internal("num_bytes empty"))?;
                         nums_encoded_as_fixed_width_le_u8.push(0u8);
                         let mut curr_num_bytes = 1;
                         while curr_num_bytes < bytes_required {
@@ -310,34 +774,40 @@ pub(super) fn parse_events<R: std::io::Read>(
                         }
                         Ok(())
                     }
-                    SignalEnum::Alias { .. } => {
-                        let (f, l) = (file!(), line!());
-                        let msg = format!(
-                            "Error near {f}:{l}, a signal alias should not point to a signal alias.\n\
-                             This error occurred while parsing vcd file at {cursor:?}");
-                        Err(msg)
-                    }
+                    SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                        "a signal alias shouldn't point to a signal alias",
+                    )),
                 }?;
+                if let Some(value) = initial_value {
+                    vcd.initial_values.push((*signal_idx, value));
+                }
             }
 
             "1" => {
+                let initial_value = state
+                    .in_dumpvars()
+                    .then(|| SignalValue::BigUint(BigUint::from(1u8)));
                 // lokup signal idx
                 let hash = &word[1..];
-                let signal_idx = signal_map.get(hash).ok_or(()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}. Failed to lookup signal {hash} at {cursor:?}",
-                        file!(),
-                        line!()
-                    )
-                })?;
+                let signal_idx = match signal_map.get(hash) {
+                    Some(signal_idx) => signal_idx,
+                    None => {
+                        let error = ParseError::UndeclaredIdentifier {
+                            identifier: hash.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
 
-                let signal = vcd.dealiasing_signal_idx_to_signal_lookup_mut(signal_idx)?;
+                let signal = changing_signal(vcd, state, signal_idx)?;
 
                 match signal {
                     SignalEnum::Data {
                         name,
-                        signal_type,
                         ref mut signal_error,
+                        loaded,
                         num_bits,
                         num_bytes,
                         nums_encoded_as_fixed_width_le_u8,
@@ -345,8 +815,8 @@ pub(super) fn parse_events<R: std::io::Read>(
                         byte_len_of_num_tmstmp_vals_on_tmln,
                         ..
                     } => {
-                        // if this is a bad signal, go ahead and skip it
-                        if signal_error.is_some() {
+                        // if this is a bad or unselected signal, go ahead and skip it
+                        if signal_error.is_some() || !*loaded {
                             continue;
                         }
 
@@ -355,44 +825,41 @@ pub(super) fn parse_events<R: std::io::Read>(
                         match num_bits {
                             Some(ref num_bits) => {
                                 if *num_bits != 1 {
-                                    let (f, l) = (file!(), line!());
-                                    let msg = format!(
-                                        "\
-                                        Error near {f}:{l}. The bitwidth for signal {name} \
-                                        of signal_type {signal_type:?} is expected to be `1` not \
-                                        `{num_bits}`. \
-                                        This error occurred while parsing the vcd file at \
-                                        {cursor:?}"
-                                    );
-                                    *signal_error = Some(msg);
+                                    let error = ParseError::WidthMismatch {
+                                        signal: name.clone(),
+                                        expected: *num_bits,
+                                        found: 1,
+                                        cursor,
+                                    };
+                                    *signal_error = Some(error.to_string());
+                                    state.recovery.note_once(vcd, Severity::Error, error);
                                     continue;
                                 }
                             }
                             None => {
-                                let (f, l) = (file!(), line!());
-                                let msg = format!(
-                                    "\
-                                    Error near {f}:{l}. The bitwidth for signal {name} \
-                                    must be specified for a signal of type {signal_type:?}. \
-                                    This error occurred while parsing the vcd file at \
-                                    {cursor:?}"
-                                );
-                                Err(msg)?;
+                                let error = ParseError::MissingWidth {
+                                    signal: name.clone(),
+                                    cursor,
+                                };
+                                // like a width mismatch, this only spoils the
+                                // one signal when parsing leniently
+                                *signal_error = Some(error.to_string());
+                                state.recovery.recover_once(vcd, Severity::Error, error)?;
+                                continue;
                             }
                         };
                         // timestamp stuff
                         lsb_indxs_of_num_tmstmp_vals_on_tmln
-                            .push(LsbIdxOfTmstmpValOnTmln(curr_tmstmp_lsb_idx));
-                        byte_len_of_num_tmstmp_vals_on_tmln.push(curr_tmstmp_len_u8);
+                            .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                        byte_len_of_num_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
 
                         // value stuff
                         // we may need to zero extend values
                         // so that we end up storing all values
                         // of a particular signal in a consistent
                         // amount of bytes
-                        let bytes_required = num_bytes.ok_or_else(|| {
-                            format!("Error near {}:{}. num_bytes empty.", file!(), line!())
-                        })?;
+                        let bytes_required =
+                            num_bytes.ok_or_else(|| ParseError::internal("num_bytes empty"))?;
                         nums_encoded_as_fixed_width_le_u8.push(1u8);
                         let mut curr_num_bytes = 1;
                         while curr_num_bytes < bytes_required {
@@ -401,45 +868,57 @@ pub(super) fn parse_events<R: std::io::Read>(
                         }
                         Ok(())
                     }
-                    SignalEnum::Alias { .. } => {
-                        let (f, l) = (file!(), line!());
-                        let msg = format!(
-                            "Error near {f}:{l}, a signal alias should not point to a signal alias.\n\
-                             This error occurred while parsing vcd file at {cursor:?}");
-                        Err(msg)
-                    }
+                    SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                        "a signal alias shouldn't point to a signal alias",
+                    )),
                 }?;
+                if let Some(value) = initial_value {
+                    vcd.initial_values.push((*signal_idx, value));
+                }
             }
 
             // other one bit cases
             "x" | "X" | "z" | "Z" | "u" | "U" | "h" | "H" | "l" | "L" | "w" | "W" | "-" => {
+                // as with vectors, nine valued logic isn't part of the standard
+                if !matches!(first_letter, "x" | "X" | "z" | "Z") {
+                    state
+                        .recovery
+                        .nitpick(vcd, Severity::Warning, || bad_value(word, cursor));
+                }
                 // Select value and turn to lowercase for consistency
                 let val = word[0..1].to_ascii_lowercase();
+                let initial_value = state
+                    .in_dumpvars()
+                    .then(|| SignalValue::String(val.clone()));
                 // lokup signal idx
                 let hash = &word[1..];
-                let signal_idx = signal_map.get(hash).ok_or(()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}. Failed to lookup signal {hash} at {cursor:?}",
-                        file!(),
-                        line!()
-                    )
-                })?;
+                let signal_idx = match signal_map.get(hash) {
+                    Some(signal_idx) => signal_idx,
+                    None => {
+                        let error = ParseError::UndeclaredIdentifier {
+                            identifier: hash.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
 
-                let signal = vcd.dealiasing_signal_idx_to_signal_lookup_mut(signal_idx)?;
+                let signal = changing_signal(vcd, state, signal_idx)?;
 
                 match signal {
                     SignalEnum::Data {
                         name,
-                        signal_type,
                         ref mut signal_error,
+                        loaded,
                         num_bits,
                         string_vals,
                         byte_len_of_string_tmstmp_vals_on_tmln,
                         lsb_indxs_of_string_tmstmp_vals_on_tmln,
                         ..
                     } => {
-                        // if this is a bad signal, go ahead and skip it
-                        if signal_error.is_some() {
+                        // if this is a bad or unselected signal, go ahead and skip it
+                        if signal_error.is_some() || !*loaded {
                             continue;
                         }
 
@@ -448,97 +927,247 @@ pub(super) fn parse_events<R: std::io::Read>(
                         match num_bits {
                             Some(ref num_bits) => {
                                 if *num_bits != 1 {
-                                    let (f, l) = (file!(), line!());
-                                    let msg = format!(
-                                        "\
-                                        Error near {f}:{l}. The bitwidth for signal {name} \
-                                        of signal_type {signal_type:?} is expected to be `1` not \
-                                        `{num_bits}`. \
-                                        This error occurred while parsing the vcd file at \
-                                        {cursor:?}"
-                                    );
-                                    *signal_error = Some(msg);
+                                    let error = ParseError::WidthMismatch {
+                                        signal: name.clone(),
+                                        expected: *num_bits,
+                                        found: 1,
+                                        cursor,
+                                    };
+                                    *signal_error = Some(error.to_string());
+                                    state.recovery.note_once(vcd, Severity::Error, error);
                                     continue;
                                 }
                             }
                             None => {
-                                let (f, l) = (file!(), line!());
-                                let msg = format!(
-                                    "\
-                                    Error near {f}:{l}. The bitwidth for signal {name} \
-                                    must be specified for a signal of type {signal_type:?}. \
-                                    This error occurred while parsing the vcd file at \
-                                    {cursor:?}"
-                                );
-                                Err(msg)?;
+                                let error = ParseError::MissingWidth {
+                                    signal: name.clone(),
+                                    cursor,
+                                };
+                                // like a width mismatch, this only spoils the
+                                // one signal when parsing leniently
+                                *signal_error = Some(error.to_string());
+                                state.recovery.recover_once(vcd, Severity::Error, error)?;
+                                continue;
                             }
                         };
 
                         // record timestamp at which this event occurs
                         lsb_indxs_of_string_tmstmp_vals_on_tmln
-                            .push(LsbIdxOfTmstmpValOnTmln(curr_tmstmp_lsb_idx));
-                        byte_len_of_string_tmstmp_vals_on_tmln.push(curr_tmstmp_len_u8);
+                            .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                        byte_len_of_string_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
 
                         // record value
                         string_vals.push(val);
                         Ok(())
                     }
-                    SignalEnum::Alias { .. } => {
-                        let (f, l) = (file!(), line!());
-                        let msg = format!(
-                            "Error near {f}:{l}, a signal alias should not point to a signal alias.\n\
-                             This error occurred while parsing vcd file at {cursor:?}");
-                        Err(msg)
-                    }
+                    SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                        "a signal alias shouldn't point to a signal alias",
+                    )),
                 }?;
+                if let Some(value) = initial_value {
+                    vcd.initial_values.push((*signal_idx, value));
+                }
             }
-            // Store real values as a string as well and let the user parse it to an f64
-            "s" | " S" | "r" | "R" => {
+            // strings, and reals which are kept as f64s unless the signal
+            // isn't a real
+            "s" | "S" | "r" | "R" => {
                 let val = word[1..].to_string();
-                let (hash, cursor) = next_word!(word_reader)?;
+                let real = match first_letter {
+                    "r" | "R" => match val.parse::<f64>() {
+                        Ok(real) => Some(real),
+                        Err(_) => {
+                            let error = ParseError::BadValue {
+                                value: word.to_string(),
+                                cursor,
+                            };
+                            state.recovery.recover_once(vcd, Severity::Error, error)?;
+                            // skip the identifier the value was meant for
+                            word_reader.next_word();
+                            continue;
+                        }
+                    },
+                    _ => None,
+                };
+                let (hash, cursor) = match next_word!(word_reader) {
+                    Ok(word) => word,
+                    Err(error) => {
+                        state.recovery.recover_once(vcd, Severity::Warning, error)?;
+                        continue;
+                    }
+                };
                 // lokup signal idx
-                let signal_idx = signal_map.get(hash).ok_or(()).map_err(|_| {
-                    format!(
-                        "Error near {}:{}. Failed to lookup signal {hash} at {cursor:?}",
-                        file!(),
-                        line!()
-                    )
+                let signal_idx = match signal_map.get(hash) {
+                    Some(signal_idx) => signal_idx,
+                    None => {
+                        let error = ParseError::UndeclaredIdentifier {
+                            identifier: hash.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
+
+                let signal = changing_signal(vcd, state, signal_idx)?;
+
+                let initial_value = match signal {
+                    SignalEnum::Data {
+                        ref mut signal_error,
+                        loaded,
+                        signal_type,
+                        string_vals,
+                        byte_len_of_string_tmstmp_vals_on_tmln,
+                        lsb_indxs_of_string_tmstmp_vals_on_tmln,
+                        nums_encoded_as_fixed_width_le_u8,
+                        byte_len_of_num_tmstmp_vals_on_tmln,
+                        lsb_indxs_of_num_tmstmp_vals_on_tmln,
+                        ..
+                    } => {
+                        // if this is a bad or unselected signal, go ahead and skip it
+                        if signal_error.is_some() || !*loaded {
+                            continue;
+                        }
+
+                        // the values of reals are kept as the bits of an
+                        // f64, and every other value as a string
+                        match real.filter(|_| signal_type.is_real()) {
+                            Some(real) => {
+                                lsb_indxs_of_num_tmstmp_vals_on_tmln
+                                    .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                                byte_len_of_num_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
+                                nums_encoded_as_fixed_width_le_u8
+                                    .extend_from_slice(&real.to_bits().to_le_bytes());
+                                Ok(state.in_dumpvars().then_some(SignalValue::Real(real)))
+                            }
+                            None => {
+                                lsb_indxs_of_string_tmstmp_vals_on_tmln
+                                    .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                                byte_len_of_string_tmstmp_vals_on_tmln
+                                    .push(state.curr_tmstmp_len_u8);
+                                let initial_value = state
+                                    .in_dumpvars()
+                                    .then(|| SignalValue::String(val.clone()));
+                                string_vals.push(val);
+                                Ok(initial_value)
+                            }
+                        }
+                    }
+                    SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                        "a signal alias shouldn't point to a signal alias",
+                    )),
+                }?;
+                if let Some(value) = initial_value {
+                    vcd.initial_values.push((*signal_idx, value));
+                }
+            }
+            // the value changes of the ports of extended VCDs, such as
+            // `pDZ 6 0 <12`, which are stored as strings
+            "p" => {
+                let states = word.to_string();
+                let mut strengths = vec![];
+                while strengths.len() < 2 {
+                    match next_word!(word_reader) {
+                        Ok((strength, _)) => strengths.push(strength.to_string()),
+                        Err(error) => {
+                            state.recovery.recover_once(vcd, Severity::Warning, error)?;
+                            break;
+                        }
+                    }
+                }
+                let [strength0, strength1] = &strengths[..] else {
+                    continue;
+                };
+                let Some(value) = PortValue::parse(&states[1..], strength0, strength1) else {
+                    let error = ParseError::BadValue {
+                        value: format!("{states} {strength0} {strength1}"),
+                        cursor,
+                    };
+                    state.recovery.recover_once(vcd, Severity::Error, error)?;
+                    // skip the identifier the value was meant for
+                    word_reader.next_word();
+                    continue;
+                };
+                let observed_num_bits = u32::try_from(value.states.len()).map_err(|_| {
+                    ParseError::limit("value has more than 2^32 - 1 bits", Some(cursor))
                 })?;
+                let initial_value = state
+                    .in_dumpvars()
+                    .then(|| SignalValue::Port(value.clone()));
+
+                let (hash, cursor) = match next_word!(word_reader) {
+                    Ok(word) => word,
+                    Err(error) => {
+                        state.recovery.recover_once(vcd, Severity::Warning, error)?;
+                        continue;
+                    }
+                };
+                // lookup signal idx
+                let signal_idx = match signal_map.get(hash) {
+                    Some(signal_idx) => signal_idx,
+                    None => {
+                        let error = ParseError::UndeclaredIdentifier {
+                            identifier: hash.to_string(),
+                            cursor,
+                        };
+                        state.recovery.recover_once(vcd, Severity::Error, error)?;
+                        continue;
+                    }
+                };
 
-                let signal = vcd.dealiasing_signal_idx_to_signal_lookup_mut(signal_idx)?;
+                let signal = changing_signal(vcd, state, signal_idx)?;
 
                 match signal {
                     SignalEnum::Data {
+                        name,
                         ref mut signal_error,
+                        loaded,
+                        num_bits,
                         string_vals,
                         byte_len_of_string_tmstmp_vals_on_tmln,
                         lsb_indxs_of_string_tmstmp_vals_on_tmln,
                         ..
                     } => {
-                        // if this is a bad signal, go ahead and skip it
-                        if signal_error.is_some() {
+                        // if this is a bad or unselected signal, go ahead and skip it
+                        if signal_error.is_some() || !*loaded {
                             continue;
                         }
 
+                        // like vectors, ports may not have more bits than
+                        // they were declared with
+                        if let Some(num_bits) = *num_bits {
+                            if observed_num_bits > num_bits {
+                                let error = ParseError::WidthMismatch {
+                                    signal: name.clone(),
+                                    expected: num_bits,
+                                    found: observed_num_bits,
+                                    cursor,
+                                };
+                                *signal_error = Some(error.to_string());
+                                state.recovery.note_once(vcd, Severity::Error, error);
+                                continue;
+                            }
+                        }
+
                         // record timestamp at which this event occurs
                         lsb_indxs_of_string_tmstmp_vals_on_tmln
-                            .push(LsbIdxOfTmstmpValOnTmln(curr_tmstmp_lsb_idx));
-                        byte_len_of_string_tmstmp_vals_on_tmln.push(curr_tmstmp_len_u8);
+                            .push(LsbIdxOfTmstmpValOnTmln(state.curr_tmstmp_lsb_idx));
+                        byte_len_of_string_tmstmp_vals_on_tmln.push(state.curr_tmstmp_len_u8);
 
-                        // record string value
-                        string_vals.push(val);
+                        // record value
+                        string_vals.push(value.to_string());
                         Ok(())
                     }
-                    SignalEnum::Alias { .. } => {
-                        let (f, l) = (file!(), line!());
-                        let msg = format!(
-                            "Error near {f}:{l}, a signal alias should not point to a signal alias.\n\
-                             This error occurred while parsing vcd file at {cursor:?}");
-                        Err(msg)
-                    }
+                    SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                        "a signal alias shouldn't point to a signal alias",
+                    )),
                 }?;
+                if let Some(value) = initial_value {
+                    vcd.initial_values.push((*signal_idx, value));
+                }
             }
-            _ => {}
+            _ => state
+                .recovery
+                .nitpick(vcd, Severity::Error, || bad_value(word, cursor)),
         }
     }
 
@@ -0,0 +1,117 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::fmt;
+use std::sync::Arc;
+
+use super::super::signal::{Signal, SignalEnum};
+use super::super::types::{SignalIdx, VCD};
+
+/// Picks the signals whose value changes get recorded. Value changes of
+/// every other signal are skipped, and querying those signals fails with
+/// ``SignalErrors::NotLoaded``.
+#[derive(Clone)]
+pub struct SignalFilter(FilterKind);
+
+#[derive(Clone)]
+enum FilterKind {
+    Predicate(Arc<dyn Fn(&Signal) -> bool + Send + Sync>),
+    Globs(Vec<String>),
+}
+
+impl SignalFilter {
+    /// Selects the signals for which ``predicate`` returns true.
+    pub fn from_predicate(predicate: impl Fn(&Signal) -> bool + Send + Sync + 'static) -> Self {
+        SignalFilter(FilterKind::Predicate(Arc::new(predicate)))
+    }
+
+    /// Selects the signals whose dotted hierarchical name, such as
+    /// `top.cpu.pc`, matches any of ``globs``. A `*` matches any run of
+    /// characters, and a `?` matches a single character. As `*` matches
+    /// dots too, it doesn't stop at scopes: `top.*` selects every signal
+    /// below `top`, however deeply nested, and `top.*.pc` matches
+    /// `top.cpu.pc` as well as `top.soc.cpu.pc`.
+    pub fn from_globs<I, S>(globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        SignalFilter(FilterKind::Globs(
+            globs.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    fn selects(&self, signal: &Signal) -> bool {
+        match &self.0 {
+            FilterKind::Predicate(predicate) => predicate(signal),
+            FilterKind::Globs(globs) => {
+                // a signal's path ends with the signal's own name
+                let full_name = signal.path().join(".");
+                globs.iter().any(|glob| glob_matches(glob, &full_name))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SignalFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            FilterKind::Predicate(_) => f.write_str("SignalFilter::Predicate(..)"),
+            FilterKind::Globs(globs) => f.debug_tuple("SignalFilter::Globs").field(globs).finish(),
+        }
+    }
+}
+
+/// Marks every signal that isn't selected by ``filter`` as not loaded. An
+/// aliased signal is loaded if any one of its aliases is selected, and the
+/// bits of an assembled bus are loaded if the bus is selected.
+pub(super) fn apply_signal_filter(vcd: &mut VCD, filter: &SignalFilter) {
+    let mut selected = vec![false; vcd.all_signals.len()];
+    for signal in &vcd.all_signals {
+        let signal = Signal(signal);
+        if filter.selects(&signal) {
+            for SignalIdx(idx) in signal.data_idxs(vcd) {
+                selected[idx] = true;
+            }
+        }
+    }
+
+    for (signal, selected) in vcd.all_signals.iter_mut().zip(selected) {
+        if let SignalEnum::Data { loaded, .. } = signal {
+            *loaded = selected;
+        }
+    }
+}
+
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let (glob, text): (Vec<char>, Vec<char>) = (glob.chars().collect(), text.chars().collect());
+    let (mut glob_idx, mut text_idx) = (0, 0);
+    // where to resume if the text following the most recent `*` stops
+    // matching: the glob just past the `*`, and the text that `*` has yet
+    // to swallow
+    let mut backtrack = None;
+
+    while text_idx < text.len() {
+        match glob.get(glob_idx) {
+            Some('*') => {
+                backtrack = Some((glob_idx + 1, text_idx));
+                glob_idx += 1;
+            }
+            Some(chr) if *chr == '?' || *chr == text[text_idx] => {
+                glob_idx += 1;
+                text_idx += 1;
+            }
+            _ => match backtrack {
+                Some((star_glob_idx, star_text_idx)) => {
+                    backtrack = Some((star_glob_idx, star_text_idx + 1));
+                    glob_idx = star_glob_idx;
+                    text_idx = star_text_idx + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    glob[glob_idx..].iter().all(|chr| *chr == '*')
+}
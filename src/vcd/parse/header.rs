@@ -0,0 +1,104 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use super::super::error::ParseError;
+use super::super::reader::{WordReader, WordSource};
+use super::super::types::{Metadata, SignalIdx, VCD};
+use super::events::{finish_events, parse_events, EventParserState};
+use super::options::ParseOptions;
+use super::progress::ProgressMonitor;
+use super::{compression, parse_header, rework_signals};
+
+/// The metadata, scopes and signals of a VCD, parsed without reading any
+/// of its value changes. ``finish`` picks up reading where the header
+/// left off to build the complete ``VCD``.
+pub struct VCDHeader<R: Read> {
+    vcd: VCD,
+    signal_map: HashMap<String, SignalIdx>,
+    word_reader: WordReader<R>,
+}
+
+/// Parses everything up to and including `$enddefinitions $end` and stops
+/// reading there.
+pub fn parse_vcd_header<R: Read>(file: R) -> Result<VCDHeader<R>, ParseError> {
+    let mut word_reader = WordReader::new(file);
+    let (vcd, signal_map) = parse_header(&mut word_reader, &ProgressMonitor::disabled(), false)?;
+
+    Ok(VCDHeader {
+        vcd,
+        signal_map,
+        word_reader,
+    })
+}
+
+/// Like ``parse_vcd_header``, but opens the file at ``path``, decompressing
+/// it if need be.
+pub fn parse_vcd_header_from_path(
+    path: impl AsRef<Path>,
+) -> Result<VCDHeader<Box<dyn Read>>, ParseError> {
+    let path = path.as_ref();
+    let mut file =
+        File::open(path).map_err(|e| ParseError::io(format_args!("failed to open {path:?}"), e))?;
+
+    match compression::detect_compression(&mut file)? {
+        Some(compression) => parse_vcd_header(compression::decompress(file, compression)?),
+        None => parse_vcd_header(Box::new(file) as Box<dyn Read>),
+    }
+}
+
+impl<R: Read> VCDHeader<R> {
+    pub fn metadata(&self) -> &Metadata {
+        &self.vcd.metadata
+    }
+
+    /// The scopes and signals declared in the header. None of the signals
+    /// have any values yet, and the options that rework them, such as
+    /// ``assemble_buses``, have yet to be applied.
+    pub fn vcd(&self) -> &VCD {
+        &self.vcd
+    }
+
+    /// Parses the value changes that follow the header.
+    pub fn finish(self) -> Result<VCD, ParseError> {
+        self.finish_with_options(&ParseOptions::default())
+    }
+
+    /// Like ``finish``, but reports progress, can be cancelled, can skip
+    /// signals and can recover from malformed value changes. A natural fit
+    /// for a ``SignalFilter`` built from the signals the user picked out of
+    /// ``vcd``. The value changes are always parsed on the calling thread.
+    pub fn finish_with_options(mut self, options: &ParseOptions) -> Result<VCD, ParseError> {
+        rework_signals(&mut self.vcd, options);
+
+        let monitor = ProgressMonitor::new(options, None);
+        // account for the header, so that byte counts are relative to the
+        // start of the file
+        monitor.report(self.word_reader.bytes_consumed(), &self.vcd)?;
+        let mut state = EventParserState::new(options.lenient, options.timestamp_policy);
+        parse_events(
+            &mut self.word_reader,
+            &mut self.vcd,
+            &self.signal_map,
+            &mut state,
+            &monitor,
+        )?;
+        finish_events(&mut self.vcd, &state);
+
+        Ok(self.vcd)
+    }
+}
+
+impl<R: Read> fmt::Debug for VCDHeader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VCDHeader")
+            .field("vcd", &self.vcd)
+            .finish_non_exhaustive()
+    }
+}
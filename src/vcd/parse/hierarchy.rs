@@ -0,0 +1,105 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use super::super::types::{Scope, ScopeIdx, ScopeType, SignalIdx, VCD};
+
+/// Moves every signal with a hierarchical name, such as `top.core.valid`
+/// or `top/core/valid`, into the scopes its name goes through, creating
+/// the scopes that weren't declared with a `$scope`. The scopes created
+/// are of type ``ScopeType::Module``.
+pub(super) fn split_hierarchical_names(vcd: &mut VCD) {
+    // scopes created along the way only ever get signals whose names were
+    // already split
+    for scope_idx in 0..vcd.all_scopes.len() {
+        let child_signals = std::mem::take(&mut vcd.all_scopes[scope_idx].child_signals);
+        let mut kept = Vec::with_capacity(child_signals.len());
+
+        for signal_idx in child_signals {
+            let signal = vcd.signal_from_signal_idx(signal_idx);
+            let mut segments = split_hierarchy(&signal.name());
+            let Some(name) = segments.pop().filter(|_| !segments.is_empty()) else {
+                kept.push(signal_idx);
+                continue;
+            };
+
+            let mut path = signal.path().to_vec();
+            path.pop();
+            let mut parent = ScopeIdx(scope_idx);
+            for segment in segments {
+                parent = child_scope(vcd, parent, segment.clone());
+                path.push(segment);
+            }
+            path.push(name.clone());
+
+            let SignalIdx(idx) = signal_idx;
+            vcd.all_signals[idx].rename(name, path);
+            let ScopeIdx(parent) = parent;
+            vcd.all_scopes[parent].child_signals.push(signal_idx);
+        }
+
+        vcd.all_scopes[scope_idx].child_signals = kept;
+    }
+}
+
+/// The child scope of ``parent`` named ``name``, which is created if it
+/// doesn't exist yet.
+fn child_scope(vcd: &mut VCD, parent: ScopeIdx, name: String) -> ScopeIdx {
+    let ScopeIdx(parent_idx) = parent;
+    let existing = vcd.all_scopes[parent_idx]
+        .child_scopes
+        .iter()
+        .find(|ScopeIdx(idx)| vcd.all_scopes[*idx].name == name);
+    if let Some(scope_idx) = existing {
+        return *scope_idx;
+    }
+
+    let scope_idx = ScopeIdx(vcd.all_scopes.len());
+    vcd.all_scopes.push(Scope {
+        name,
+        scope_type: ScopeType::Module,
+        self_idx: scope_idx,
+        child_signals: vec![],
+        child_scopes: vec![],
+        comments: vec![],
+    });
+    vcd.all_scopes[parent_idx].child_scopes.push(scope_idx);
+    scope_idx
+}
+
+/// Splits ``name`` at every `.` and `/`. An escaped identifier, which
+/// starts with a backslash and runs until the next whitespace, is never
+/// split, and keeps its backslash but not the whitespace that ends it.
+/// Names with an empty part, such as `top..valid`, aren't split at all.
+fn split_hierarchy(name: &str) -> Vec<String> {
+    let mut segments = vec![];
+    let mut segment = String::new();
+    let mut chars = name.chars().peekable();
+
+    while let Some(chr) = chars.next() {
+        match chr {
+            '\\' if segment.is_empty() => {
+                segment.push(chr);
+                while let Some(chr) = chars.next_if(|chr| !chr.is_whitespace()) {
+                    segment.push(chr);
+                }
+                // the whitespace ending the identifier is only dropped when
+                // a separator or the end of the name follows
+                let mut rest = chars.clone();
+                if rest.next().is_some_and(char::is_whitespace)
+                    && matches!(rest.peek(), None | Some('.' | '/'))
+                {
+                    chars = rest;
+                }
+            }
+            '.' | '/' => segments.push(std::mem::take(&mut segment)),
+            _ => segment.push(chr),
+        }
+    }
+    segments.push(segment);
+
+    if segments.iter().any(String::is_empty) {
+        return vec![name.to_string()];
+    }
+    segments
+}
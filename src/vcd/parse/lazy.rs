@@ -0,0 +1,409 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+
+/// Parses a VCD in two phases. The first pass builds the hierarchy and the
+/// timeline, and splits the value change section into blocks that each
+/// start on a timestamp, noting for every signal the blocks in which it
+/// changes. The value changes of a signal are decoded the first time the
+/// signal is queried, by replaying only its blocks against the timeline
+/// of the first pass, and kept around in a least recently used cache.
+use num::BigUint;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::super::error::ParseError;
+use super::super::reader::{ReaderPosition, SliceWordReader};
+use super::super::signal::SignalEnum;
+use super::super::types::{SignalIdx, VCD};
+use super::events::{finish_events, parse_events, EventParserState};
+use super::options::ParseOptions;
+use super::parallel::{append_chunk, empty_copy};
+use super::progress::ProgressMonitor;
+use super::{compression, parse_header, rework_signals};
+
+/// The number of bytes of value changes after which a new block starts at
+/// the next timestamp.
+const BLOCK_LEN: usize = 64 * 1024;
+
+/// Parses the hierarchy and timeline of the VCD at ``path``, deferring the
+/// value changes of each signal until the signal is first queried. At most
+/// ``cache_capacity`` signals are kept decoded at a time.
+///
+/// Decoding a signal only parses the parts of the file in which it changes
+/// value, on up to ``options.threads`` threads. Use ``VCD::load_signals``
+/// to decode several signals in one go. Signals left out by
+/// ``options.signal_filter`` are never decoded. Problems that only concern
+/// the values of a signal, such as a value that is wider than the signal,
+/// are only found once the signal is decoded.
+pub fn parse_vcd_from_path_lazily(
+    path: impl AsRef<Path>,
+    options: &ParseOptions,
+    cache_capacity: usize,
+) -> Result<VCD, ParseError> {
+    let source = Source::open(path.as_ref())?;
+
+    let mut word_gen = SliceWordReader::new(&source);
+    let monitor = ProgressMonitor::new(options, Some(source.len()));
+    let (mut vcd, signal_map) = parse_header(&mut word_gen, &monitor, options.lenient)?;
+    rework_signals(&mut vcd, options);
+
+    // the signal filter marks the signals that may be decoded later on
+    let mut selected = vec![false; vcd.all_signals.len()];
+    for (signal, selected) in vcd.all_signals.iter_mut().zip(&mut selected) {
+        if let SignalEnum::Data { loaded, .. } = signal {
+            *selected = *loaded;
+            *loaded = false;
+        }
+    }
+
+    // with no signal loaded, parsing the value changes merely builds the
+    // timeline and the index
+    let mut state = EventParserState::indexing(
+        options.lenient,
+        options.timestamp_policy,
+        &vcd,
+        word_gen.position(),
+    );
+    parse_events(&mut word_gen, &mut vcd, &signal_map, &mut state, &monitor)?;
+    finish_events(&mut vcd, &state);
+
+    let lazy = LazyLoader {
+        source,
+        index: state.index.take().unwrap_or_default(),
+        signal_map,
+        selected,
+        sorted: state.out_of_order,
+        threads: options.thread_count(),
+        cache: Mutex::new(SignalCache {
+            capacity: cache_capacity.max(1),
+            recently_used: VecDeque::new(),
+            signals: HashMap::new(),
+        }),
+    };
+
+    // the values of `$dumpvars` blocks are the only ones decoded up front
+    let all_selected: Vec<SignalIdx> = (0..lazy.selected.len())
+        .filter(|idx| lazy.selected[*idx])
+        .map(SignalIdx)
+        .collect();
+    let dumpvars_blocks = lazy.index.dumpvars_blocks.clone();
+    vcd.initial_values = lazy
+        .decode(&vcd, &all_selected, &dumpvars_blocks)?
+        .initial_values;
+    vcd.lazy = Some(lazy);
+
+    Ok(vcd)
+}
+
+/// A place in the value change section at which parsing can pick up again:
+/// the position of a timestamp word, along with what the parser knew right
+/// before reading it.
+#[derive(Debug, Clone)]
+pub(super) struct Block {
+    pub(super) position: ReaderPosition,
+    pub(super) state: EventParserState,
+    pub(super) largest_timestamp: Option<BigUint>,
+}
+
+/// What the first pass over a lazily parsed VCD learns about its value
+/// change section.
+#[derive(Debug, Default, Clone)]
+pub(super) struct EventIndex {
+    /// In file order. Every block but the first starts on a timestamp, and
+    /// runs until the next block starts.
+    blocks: Vec<Block>,
+    /// For every data signal, the blocks in which it changes value, in
+    /// file order.
+    signal_blocks: Vec<Vec<u32>>,
+    /// The blocks containing a `$dumpvars`.
+    dumpvars_blocks: Vec<u32>,
+}
+
+impl EventIndex {
+    pub(super) fn new(first_block: Block, num_signals: usize) -> EventIndex {
+        EventIndex {
+            blocks: vec![first_block],
+            signal_blocks: vec![vec![]; num_signals],
+            dumpvars_blocks: vec![],
+        }
+    }
+
+    /// Whether a timestamp at ``offset`` starts a new block.
+    pub(super) fn is_due(&self, offset: usize) -> bool {
+        self.blocks
+            .last()
+            .is_none_or(|block| offset - block.position.offset >= BLOCK_LEN)
+    }
+
+    pub(super) fn push_block(&mut self, block: Block) {
+        self.blocks.push(block);
+    }
+
+    fn curr_block(&self) -> u32 {
+        // blocks are at least ``BLOCK_LEN`` bytes apart, so there are far
+        // fewer than 2^32 of them
+        u32::try_from(self.blocks.len() - 1).unwrap_or(u32::MAX)
+    }
+
+    pub(super) fn note_change(&mut self, SignalIdx(idx): SignalIdx) {
+        let curr_block = self.curr_block();
+        if let Some(blocks) = self.signal_blocks.get_mut(idx) {
+            if blocks.last() != Some(&curr_block) {
+                blocks.push(curr_block);
+            }
+        }
+    }
+
+    pub(super) fn note_dumpvars(&mut self) {
+        let curr_block = self.curr_block();
+        if self.dumpvars_blocks.last() != Some(&curr_block) {
+            self.dumpvars_blocks.push(curr_block);
+        }
+    }
+}
+
+enum Source {
+    Mapped(memmap2::Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Source {
+    fn open(path: &Path) -> Result<Source, ParseError> {
+        let mut file = File::open(path)
+            .map_err(|e| ParseError::io(format_args!("failed to open {path:?}"), e))?;
+
+        // compressed files can't be mapped, so we keep the decompressed
+        // dump in memory instead
+        if let Some(compression) = compression::detect_compression(&mut file)? {
+            let mut bytes = vec![];
+            compression::decompress(file, compression)?
+                .read_to_end(&mut bytes)
+                .map_err(|e| ParseError::io(format_args!("failed to decompress {path:?}"), e))?;
+            return Ok(Source::Owned(bytes));
+        }
+
+        // mapping an empty file fails on some platforms
+        let len = file
+            .metadata()
+            .map_err(|e| ParseError::io(format_args!("failed to stat {path:?}"), e))?
+            .len();
+        if len == 0 {
+            return Ok(Source::Owned(vec![]));
+        }
+
+        // SAFETY: unlike ``parse_vcd_from_path``, we hold on to the mapping
+        // for as long as the ``VCD`` lives. As with any mmap based reader,
+        // another process truncating the file in the meantime is undefined
+        // behavior.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }
+            .map_err(|e| ParseError::io(format_args!("failed to map {path:?}"), e))?;
+        Ok(Source::Mapped(mmap))
+    }
+}
+
+impl Deref for Source {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Source::Mapped(mmap) => mmap,
+            Source::Owned(bytes) => bytes,
+        }
+    }
+}
+
+/// Decodes the value changes of signals of a lazily parsed ``VCD``.
+pub(crate) struct LazyLoader {
+    source: Source,
+    index: EventIndex,
+    signal_map: HashMap<String, SignalIdx>,
+    /// The data signals selected by the signal filter, which are the only
+    /// ones that may be decoded.
+    selected: Vec<bool>,
+    /// Whether the first pass recorded blocks out of order, so that the
+    /// value changes of decoded signals have to be sorted.
+    sorted: bool,
+    threads: usize,
+    cache: Mutex<SignalCache>,
+}
+
+struct SignalCache {
+    capacity: usize,
+    /// Least recently used first.
+    recently_used: VecDeque<SignalIdx>,
+    signals: HashMap<SignalIdx, Arc<SignalEnum>>,
+}
+
+impl SignalCache {
+    fn get(&mut self, idx: SignalIdx) -> Option<Arc<SignalEnum>> {
+        let signal = self.signals.get(&idx)?.clone();
+        self.touch(idx);
+        Some(signal)
+    }
+
+    fn insert(&mut self, idx: SignalIdx, signal: Arc<SignalEnum>) {
+        self.signals.insert(idx, signal);
+        self.touch(idx);
+        while self.recently_used.len() > self.capacity {
+            if let Some(evicted) = self.recently_used.pop_front() {
+                self.signals.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, idx: SignalIdx) {
+        self.recently_used.retain(|used| *used != idx);
+        self.recently_used.push_back(idx);
+    }
+}
+
+impl LazyLoader {
+    /// Whether the data signal at ``idx`` was selected by the signal filter.
+    pub(crate) fn selects(&self, SignalIdx(idx): SignalIdx) -> bool {
+        self.selected.get(idx).copied().unwrap_or(false)
+    }
+
+    /// Returns the data signal at ``idx`` with all of its value changes,
+    /// decoding them if they aren't cached.
+    pub(crate) fn signal(&self, vcd: &VCD, idx: SignalIdx) -> Result<Arc<SignalEnum>, ParseError> {
+        if let Some(signal) = self.cache.lock().unwrap().get(idx) {
+            return Ok(signal);
+        }
+
+        self.load(vcd, &[idx])?;
+        self.cache
+            .lock()
+            .unwrap()
+            .get(idx)
+            .ok_or_else(|| ParseError::internal("a loaded signal is missing from the cache"))
+    }
+
+    /// Decodes the value changes of every selected data signal in ``idxs``
+    /// that isn't cached yet, parsing each block they change in once.
+    pub(crate) fn load(&self, vcd: &VCD, idxs: &[SignalIdx]) -> Result<(), ParseError> {
+        let mut wanted: Vec<SignalIdx> = {
+            let cache = self.cache.lock().unwrap();
+            idxs.iter()
+                .filter(|idx| self.selects(**idx) && !cache.signals.contains_key(idx))
+                .copied()
+                .collect()
+        };
+        // aliases of one another share a data signal
+        wanted.sort_unstable();
+        wanted.dedup();
+        if wanted.is_empty() {
+            return Ok(());
+        }
+
+        let mut blocks: Vec<u32> = wanted
+            .iter()
+            .flat_map(|SignalIdx(idx)| self.index.signal_blocks[*idx].iter().copied())
+            .collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+
+        let mut shell = self.decode(vcd, &wanted, &blocks)?;
+        if self.sorted {
+            for SignalIdx(idx) in &wanted {
+                shell.all_signals[*idx].sort_value_changes(&vcd.tmstmps_encoded_as_u8s);
+            }
+        }
+
+        let mut cache = self.cache.lock().unwrap();
+        for SignalIdx(idx) in wanted {
+            let signal = std::mem::replace(
+                &mut shell.all_signals[idx],
+                vcd.all_signals[idx].without_values(),
+            );
+            cache.insert(SignalIdx(idx), Arc::new(signal));
+        }
+
+        Ok(())
+    }
+
+    /// Replays ``blocks`` with only the data signals in ``signals`` loaded,
+    /// splitting them between ``self.threads`` threads. The value changes
+    /// recorded refer to the timeline of ``vcd``.
+    fn decode(&self, vcd: &VCD, signals: &[SignalIdx], blocks: &[u32]) -> Result<VCD, ParseError> {
+        let mut template = empty_copy(vcd);
+        for SignalIdx(idx) in signals {
+            if let Some(SignalEnum::Data { loaded, .. }) = template.all_signals.get_mut(*idx) {
+                *loaded = true;
+            }
+        }
+        if blocks.is_empty() {
+            return Ok(template);
+        }
+
+        let group_len = blocks.len().div_ceil(self.threads.max(1));
+        let decoded: Vec<Result<VCD, ParseError>> = thread::scope(|scope| {
+            let handles: Vec<_> = blocks
+                .chunks(group_len)
+                .map(|group| {
+                    let shell = empty_copy(&template);
+                    scope.spawn(move || self.replay(group, shell))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+
+        // none of the shells has a timeline of its own, so stitching them
+        // together leaves the timeline indices alone
+        let mut decoded = decoded.into_iter();
+        let mut shell = decoded.next().unwrap_or(Ok(template))?;
+        for group_shell in decoded {
+            append_chunk(&mut shell, group_shell?)?;
+        }
+        Ok(shell)
+    }
+
+    /// Parses the value changes of every block in ``blocks`` into ``shell``.
+    fn replay(&self, blocks: &[u32], mut shell: VCD) -> Result<VCD, ParseError> {
+        for block_idx in blocks {
+            let block_idx = *block_idx as usize;
+            let block = &self.index.blocks[block_idx];
+            let end = self
+                .index
+                .blocks
+                .get(block_idx + 1)
+                .map_or(self.source.len(), |next| next.position.offset);
+
+            let mut word_reader = SliceWordReader::resume(&self.source[..end], 0, block.position);
+            let mut state = block.state.clone();
+            shell.largest_timestamp = block.largest_timestamp.clone();
+            parse_events(
+                &mut word_reader,
+                &mut shell,
+                &self.signal_map,
+                &mut state,
+                &ProgressMonitor::disabled(),
+            )?;
+        }
+        Ok(shell)
+    }
+}
+
+impl fmt::Debug for LazyLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyLoader")
+            .field("blocks", &self.index.blocks.len())
+            .field("threads", &self.threads)
+            .finish_non_exhaustive()
+    }
+}
@@ -2,333 +2,233 @@
 // This program is distributed under both the GPLV3 license
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
-use chrono::prelude::{DateTime, Utc};
-use itertools::Itertools;
+use chrono::prelude::{DateTime, NaiveDateTime, Utc};
 
-use super::super::reader::{next_word, Cursor, WordReader};
-use super::super::types::{Metadata, Timescale, Version};
+use super::super::error::{Diagnostic, ParseError, Severity};
+use super::super::reader::{next_word, WordSource};
+use super::super::time::{TimeScale, TimeUnit};
+use super::super::types::{HeaderSection, Metadata, Version};
 
-use super::combinator_atoms::{digit, tag, take_until, take_while};
+use super::combinator_atoms::{digit, ident, tag, take_while};
 use super::types::ParseResult;
 
-pub(super) fn parse_date(
-    word_and_ctx1: (&str, &Cursor),
-    word_and_ctx2: (&str, &Cursor),
-    word_and_ctx3: (&str, &Cursor),
-    word_and_ctx4: (&str, &Cursor),
-    word_and_ctx5: (&str, &Cursor),
-) -> Result<DateTime<Utc>, String> {
-    let day = {
-        // check for another word in the file
-        let (word, cursor) = word_and_ctx1;
-
-        let days = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
-        if !days.contains(&word) {
-            let msg = format!("Error near {}:{}.", file!(), line!());
-            let msg2 = format!("{word} is not a valid weekday : expected one of {days:?}\n");
-            let msg3 = format!("failure location: {cursor:?}");
-            return Err(format!("{}{}{}", msg, msg2, msg3));
-        }
-
-        word.to_string()
-    };
-
-    let month = {
-        // check for another word in the file
-        let (word, cursor) = word_and_ctx2;
-
-        let months = [
-            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sept", "Oct", "Nov", "Dec",
-        ];
-
-        if !months.contains(&word) {
-            let msg = format!("Error near {}:{}.", file!(), line!());
-            let msg2 = format!("{word} is not a valid month : expected one of {months:?}\n");
-            let msg3 = format!("failure location: {cursor:?}");
-            return Err(format!("{}{}{}", msg, msg2, msg3));
-        }
-
-        word.to_string()
-    };
-
-    let date = {
-        // check for another word in the file
-        let (word, cursor) = word_and_ctx3;
-
-        let date: u8 = match word.to_string().parse() {
-            Ok(date) => date,
-            Err(e) => return Err(format!("Error near {}:{}. {e}", file!(), line!())),
-        };
-
-        if date > 31 {
-            let msg = format!("Error near {}:{}.", file!(), line!());
-            let msg2 = format!("{word} is not a valid date : must be between 0 and 31\n");
-            let msg3 = format!("failure location: {cursor:?}");
-            return Err(format!("{}{}{}", msg, msg2, msg3));
+/// The layouts of `$date` sections without a time zone written by the
+/// simulators we have come across, as ``chrono`` format strings.
+const DATE_FORMATS: [&str; 5] = [
+    // Icarus, ModelSim, Questa, VCS, GHDL, Verilator, ISim and MyHDL:
+    // `Mon Jan  4 17:57:07 2021`
+    "%a %b %e %H:%M:%S %Y",
+    // NCSim, Quartus and SystemC: `Sep 19, 2019  11:13:29`
+    "%b %d, %Y %H:%M:%S",
+    // Amaranth: `2022-07-13 18:48:57.685239`
+    "%Y-%m-%d %H:%M:%S%.f",
+    // Vivado: `2018-May-17 12:40:43`
+    "%Y-%b-%d %H:%M:%S",
+    // Quartus: `10/19/2019 09:49:22`
+    "%m/%d/%Y %H:%M:%S",
+];
+
+/// Like ``DATE_FORMATS``, but for layouts that carry a UTC offset.
+const ZONED_DATE_FORMATS: [&str; 3] = [
+    // Treadle: `2016-10-13T16:31+0000`
+    "%Y-%m-%dT%H:%M%z",
+    // ISO 8601: `2016-10-13T16:31:07.25+02:00`
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    // `date` on Unix: `Mon Jan  4 17:57:07 +0100 2021`
+    "%a %b %e %H:%M:%S %z %Y",
+];
+
+/// Parses the text of a `$date` section in any of the layouts we know of.
+/// Dates without a time zone are taken to be in UTC.
+pub(super) fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    // chrono only knows the three letter abbreviation of September, and
+    // neither knows the abbreviations of time zones
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|word| match word {
+            "Sept" => "Sep",
+            "UTC" | "GMT" | "Z" => "+0000",
+            word => word,
+        })
+        .collect();
+    let text = words.join(" ");
+
+    for format in ZONED_DATE_FORMATS {
+        if let Ok(date) = DateTime::parse_from_str(&text, format) {
+            return Some(date.with_timezone(&Utc));
         }
-
-        date.to_string()
-    };
-
-    let (hh, mm, ss) = {
-        // get hour
-        let (word, cursor) = word_and_ctx4;
-
-        let res = take_until(word, b':');
-        res.assert_match()?;
-        let hh: u8 = res
-            .matched
-            .to_string()
-            .parse()
-            .map_err(|e| format!("Error near {}:{}. {e}", file!(), line!()))?;
-
-        if hh > 23 {
-            let msg = format!("Error near {}:{}.", file!(), line!());
-            let msg2 = format!("{hh} is not a valid hour : must be between 0 and 23\n");
-            let msg3 = format!("failure location: {cursor:?}");
-            return Err(format!("{}{}{}", msg, msg2, msg3));
-        }
-
-        // get minute
-        let word = &res.residual[1..]; // chop off colon which is at index 0
-        let res = take_until(word, b':');
-        res.assert_match()?;
-        let mm: u8 = res
-            .matched
-            .to_string()
-            .parse()
-            .map_err(|e| format!("Error near {}:{}. {e}", file!(), line!()))?;
-
-        if mm > 60 {
-            let msg = format!("Error near {}:{}.", file!(), line!());
-            let msg2 = format!("{mm} is not a valid minute : must be between 0 and 60\n");
-            let msg3 = format!("failure location: {cursor:?}");
-            return Err(format!("{}{}{}", msg, msg2, msg3));
-        }
-
-        // get second
-        // let ss : u8 = remainder.to_string().parse().unwrap();
-        res.assert_residual()?;
-        let residual = &res.residual[1..]; // chop of colon which is at index 0
-        let ss: u8 = residual
-            .to_string()
-            .parse()
-            .map_err(|e| format!("Error near {}:{}. {e}", file!(), line!()))?;
-
-        if ss > 60 {
-            let msg = format!("Error near {}:{}.", file!(), line!());
-            let msg2 = format!("{ss} is not a valid second : must be between 0 and 60\n");
-            let msg3 = format!("failure location: {cursor:?}");
-            return Err(format!("{}{}{}", msg, msg2, msg3));
-        }
-        (hh.to_string(), mm.to_string(), ss.to_string())
-    };
-
-    let year = {
-        // check for another word in the file
-        let (word, _) = word_and_ctx5;
-        word.to_string()
-    };
-
-    // unfortunately, the minutes, seconds, and hour could occur in an
-    // unexpected order
-    let full_date = format!("{day} {month} {date} {hh}:{mm}:{ss} {year}");
-    let full_date = DateTime::parse_from_str(full_date.as_str(), "%a %b %e %T %Y");
-    if full_date.is_ok() {
-        return Ok(full_date.unwrap().into());
     }
-
-    Err(format!(
-        "Error near {}:{}. Failed to parse date.",
-        file!(),
-        line!()
-    ))
+    if let Ok(date) = DateTime::parse_from_rfc3339(&text) {
+        return Some(date.with_timezone(&Utc));
+    }
+    DATE_FORMATS.iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(&text, format)
+            .ok()
+            .map(|date| date.and_utc())
+    })
 }
 
-pub(super) fn parse_version<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
-) -> Result<Version, String> {
-    let mut version = String::new();
+/// Reads the text of a section up to its `$end`, with runs of whitespace
+/// collapsed into single spaces.
+pub(super) fn parse_section_text<W: WordSource>(word_reader: &mut W) -> Result<String, ParseError> {
+    let mut text = String::new();
 
     loop {
         let (word, _) = next_word!(word_reader)?;
 
         if word == "$end" {
-            // truncate trailing whitespace
-            let version = version[0..(version.len() - 1)].to_string();
-            return Ok(Version(version));
-        } else {
-            version.push_str(word);
-            version.push(' ');
+            return Ok(text);
         }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(word);
     }
 }
 
-pub(super) fn parse_timescale<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
-) -> Result<(Option<u32>, Timescale), String> {
+/// Parses a `$timescale` such as `10 ps` or `1ns`, whose magnitude must be
+/// 1, 10 or 100, up to its `$end`.
+pub(super) fn parse_timescale<W: WordSource>(word_reader: &mut W) -> Result<TimeScale, ParseError> {
     // we might see `1ps $end` or `1 ps $end`
     // first get timescale
-    let (word, _) = next_word!(word_reader)?;
+    let (word, cursor) = next_word!(word_reader)?;
     let ParseResult { matched, residual } = take_while(word, digit);
-    let scalar = matched;
-
-    let scalar: u32 = scalar
-        .to_string()
-        .parse()
-        .map_err(|e| format!("Error near {}:{}. {e}", file!(), line!()))?;
+    let magnitude = matched.parse().ok();
+    let mut timescale = word.to_string();
+
+    let unit = if residual.is_empty() {
+        let (unit, _) = next_word!(word_reader)?;
+        timescale.push(' ');
+        timescale.push_str(unit);
+        TimeUnit::from_name(unit)
+    } else {
+        TimeUnit::from_name(residual)
+    };
 
-    let timescale = {
-        if residual.is_empty() {
-            let (word, _) = next_word!(word_reader)?;
-            let unit = match word {
-                "fs" => Ok(Timescale::Fs),
-                "ps" => Ok(Timescale::Ps),
-                "ns" => Ok(Timescale::Ns),
-                "us" => Ok(Timescale::Us),
-                "ms" => Ok(Timescale::Ms),
-                "s" => Ok(Timescale::S),
-                _ => Err(format!(
-                    "Error near {}:{}. Unknown unit {word}.",
-                    file!(),
-                    line!()
-                )),
-            }?;
+    let timescale = magnitude
+        .zip(unit)
+        .and_then(|(magnitude, unit)| TimeScale::new(magnitude, unit))
+        .ok_or(ParseError::BadTimescale { timescale, cursor })?;
 
-            (Some(scalar), unit)
-        } else {
-            let unit = match residual {
-                "fs" => Ok(Timescale::Fs),
-                "ps" => Ok(Timescale::Ps),
-                "ns" => Ok(Timescale::Ns),
-                "us" => Ok(Timescale::Us),
-                "ms" => Ok(Timescale::Ms),
-                "s" => Ok(Timescale::S),
-                _ => Err(format!(
-                    "Error near {}:{}. Unknown unit {residual}.",
-                    file!(),
-                    line!()
-                )),
-            }?;
+    Ok(timescale)
+}
 
-            (Some(scalar), unit)
-        }
-    };
+/// Parses a `$timezero`, which is a signed number of ticks, up to its
+/// `$end`.
+pub(super) fn parse_timezero<W: WordSource>(word_reader: &mut W) -> Result<i64, ParseError> {
+    let (word, cursor) = next_word!(word_reader)?;
+    let timezero = word.parse().map_err(|_| ParseError::BadTimezero {
+        timezero: word.to_string(),
+        cursor,
+    })?;
 
-    // then check for the `$end` keyword
-    let (word, _) = next_word!(word_reader)?;
-    tag(word, "$end").assert_match()?;
+    Ok(timezero)
+}
 
-    Ok(timescale)
+/// Checks the `$end` of a section whose contents were parsed already. A
+/// malformed `$end` is recorded as a warning in ``diagnostics``, and
+/// doesn't undo what was parsed.
+fn parse_section_end<W: WordSource>(
+    word_reader: &mut W,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), ParseError> {
+    match ident(word_reader, "$end") {
+        Err(error @ ParseError::UnknownKeyword { .. }) => {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                error,
+            });
+            Ok(())
+        }
+        result => result,
+    }
 }
 
-pub(super) fn parse_metadata<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
-) -> Result<Metadata, String> {
+/// Parses the declarations preceding the first `$scope` or `$var`. A
+/// timescale or timezero that can't be parsed, such as `1000 ps`, is left
+/// out of the metadata rather than failing the parse, as dumps with one
+/// have always been read. Like unknown keywords, they are recorded as
+/// warnings in ``diagnostics``.
+pub(super) fn parse_metadata<W: WordSource>(
+    word_reader: &mut W,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Metadata, ParseError> {
     let mut metadata = Metadata {
         date: None,
+        raw_date: None,
         version: None,
-        timescale: (None, Timescale::Unit),
+        timescale: None,
+        timezero: None,
+        extra_sections: vec![],
     };
 
     loop {
         // check for another word in the file
-        let (word, _) = next_word!(word_reader)?;
+        let (word, cursor) = next_word!(word_reader)?;
 
         let ParseResult { matched, residual } = tag(word, "$");
-        match matched {
-            // we hope that this word stars with a `$`
-            "$" => {
-                match residual {
-                    "date" => {
-                        // a date is typically composed of the 5 following words which can
-                        // occur in any order:
-                        // {Day, Month, Date(number in month), hh:mm:ss, year}.
-                        // Thus, we must lookahead read the 5 next words, and try our date
-                        // parser on 5! = 120 permutations of the 5 words.
-                        //
-                        // It is also possible that within each permutation, the hours,
-                        // minutes, and seconds could be in an unusual order, which means
-                        // that we may search up to 6 different permutations oh hh::mm:ss,
-                        // for an upper bound total of 720 permutations
-                        //
-                        // While looking ahead, if one of the 5 words in `$end`, we have to
-                        // immediately stop trying to get more words.
-
-                        let mut found_end = false;
-                        let mut lookahead_5_words: Vec<(String, Cursor)> = Vec::new();
-
-                        for _ in 0..5 {
-                            let (word, cursor) = next_word!(word_reader)?;
-                            let word = word.to_string();
-                            match word.as_str() {
-                                "$end" => {
-                                    found_end = true;
-                                    break;
-                                }
-                                _ => {
-                                    lookahead_5_words.push((word, cursor));
-                                }
-                            };
-                        }
-
-                        // we no longer attempt to parse date if we weren't able to lookahead 5
-                        // words
-                        if found_end {
-                            continue;
-                        }
-
-                        let permutations = lookahead_5_words
-                            .iter()
-                            .permutations(lookahead_5_words.len());
-
-                        // go ahead and search for a match amongst permuted date text
-                        for mut permutations in permutations {
-                            let (w1, s1) = permutations.pop().unwrap();
-                            let arg_1 = (&w1[..], s1);
-
-                            let (w2, s2) = permutations.pop().unwrap();
-                            let arg_2 = (&w2[..], s2);
-
-                            let (w3, s3) = permutations.pop().unwrap();
-                            let arg_3 = (&w3[..], s3);
-
-                            let (w4, s4) = permutations.pop().unwrap();
-                            let arg_4 = (&w4[..], s4);
-
-                            let (w5, s5) = permutations.pop().unwrap();
-                            let arg_5 = (&w5[..], s5);
-
-                            let parsed_date = parse_date(arg_1, arg_2, arg_3, arg_4, arg_5);
-
-                            // store date and exit loop if a match is found
-                            if parsed_date.is_ok() {
-                                metadata.date = Some(parsed_date?);
-                                break;
-                            }
-                        }
-                    }
-                    "version" => {
-                        let version = parse_version(word_reader);
-                        if version.is_ok() {
-                            metadata.version = Some(version.unwrap());
-                        }
+        // we hope that this word starts with a `$`, if it does not,
+        // we keep looping
+        if matched == "$" {
+            match residual {
+                "date" => {
+                    let raw_date = parse_section_text(word_reader)?;
+                    metadata.date = parse_date(&raw_date);
+                    metadata.raw_date = Some(raw_date);
+                }
+                "version" => {
+                    metadata.version = Some(Version(parse_section_text(word_reader)?));
+                }
+                "timescale" => match parse_timescale(word_reader) {
+                    Ok(timescale) => {
+                        metadata.timescale = Some(timescale);
+                        parse_section_end(word_reader, diagnostics)?;
                     }
-                    "timescale" => {
-                        let timescale = parse_timescale(word_reader);
-                        if timescale.is_ok() {
-                            metadata.timescale = timescale.unwrap();
-                        }
+                    Err(error @ ParseError::BadTimescale { .. }) => diagnostics.push(Diagnostic {
+                        severity: Severity::Warning,
+                        error,
+                    }),
+                    Err(error) => return Err(error),
+                },
+                "timezero" => match parse_timezero(word_reader) {
+                    Ok(timezero) => {
+                        metadata.timezero = Some(timezero);
+                        parse_section_end(word_reader, diagnostics)?;
                     }
-                    "scope" => break,
-                    "var" => break,
-                    // we keep searching for words until we've found one of the following
-                    // keywords, ["version", "timescale", "scope", "var"]
-                    _ => {}
+                    Err(error @ ParseError::BadTimezero { .. }) => diagnostics.push(Diagnostic {
+                        severity: Severity::Warning,
+                        error,
+                    }),
+                    Err(error) => return Err(error),
+                },
+                "comment" => {
+                    let text = parse_section_text(word_reader)?;
+                    metadata.extra_sections.push(HeaderSection::Comment(text));
+                }
+                "scope" => break,
+                "var" => break,
+                "end" => {}
+                // sections we don't know of, such as vendor extensions, are
+                // kept as they are
+                _ => {
+                    let keyword = word.to_string();
+                    diagnostics.push(Diagnostic {
+                        severity: Severity::Warning,
+                        error: ParseError::UnknownKeyword {
+                            keyword: keyword.clone(),
+                            expected:
+                                "`$date`, `$version`, `$timescale`, `$timezero`, `$comment`, \
+                                       `$scope` or `$var`"
+                                    .to_string(),
+                            cursor,
+                        },
+                    });
+                    let text = parse_section_text(word_reader)?;
+                    metadata
+                        .extra_sections
+                        .push(HeaderSection::Unknown { keyword, text });
                 }
             }
-            // if word does not start with `$`, then we keep looping
-            _ => {}
         }
     }
     Ok(metadata)
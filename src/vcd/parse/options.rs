@@ -0,0 +1,110 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::fmt;
+
+use super::filter::SignalFilter;
+use super::progress::{CancellationToken, ProgressCallback};
+
+/// Knobs for the ``*_with_options`` family of parse entry points.
+/// ``ParseOptions::default()`` parses exactly like ``parse_vcd`` does.
+#[derive(Clone)]
+pub struct ParseOptions {
+    /// The number of threads used to parse the value change section.
+    /// Only input that is entirely in memory (a byte slice or a memory
+    /// mapped file) can be split between threads; streams are always
+    /// parsed on the calling thread. ``0`` picks one thread per available
+    /// core.
+    pub threads: usize,
+    /// Called roughly every megabyte of input with how far the parse has
+    /// got. When parsing on several threads, it is called from each of
+    /// them.
+    pub progress: Option<ProgressCallback>,
+    /// Makes the parse return an error as soon as the token is cancelled.
+    pub cancellation: Option<CancellationToken>,
+    /// Only records value changes of the signals selected by the filter.
+    /// Every signal is still declared in the resulting ``VCD``.
+    pub signal_filter: Option<SignalFilter>,
+    /// Instead of failing on the first problem in the input, skips or
+    /// repairs the offending declaration or value change, records the
+    /// problem in ``VCD::diagnostics`` and carries on. Problems in the
+    /// value change section are recorded once per distinct message.
+    /// Cancellation, I/O errors and the parser's internal limits still
+    /// fail the parse.
+    pub lenient: bool,
+    /// What to do with a timestamp that is earlier than one seen before.
+    pub timestamp_policy: TimestampPolicy,
+    /// Adds a bus to every scope for each group of single bit signals that
+    /// share a name, such as `data [0]` through `data [7]`, as simulators
+    /// write vectors that were split into bits. Querying the bus returns
+    /// the value of all its bits together, most significant bit first.
+    pub assemble_buses: bool,
+    /// Splits the names of signals such as `top.core.valid` or
+    /// `top/core/valid`, which some tools write instead of declaring
+    /// scopes, into scopes of their own. Escaped identifiers, such as
+    /// `\bus.x`, are never split.
+    pub split_hierarchical_names: bool,
+}
+
+/// How the parser deals with a dump that goes back in time. Timestamps
+/// that merely repeat the one before them are never a problem, and share
+/// a single entry on the timeline. Such dumps have always been accepted,
+/// so ``Sort`` is the default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPolicy {
+    /// Fails the parse with ``ParseError::TimestampOutOfOrder``. When
+    /// parsing leniently, the problem is recorded and the block of value
+    /// changes that follows the timestamp is dropped.
+    Reject,
+    /// Records the value changes of an out of order block at its
+    /// timestamp, and sorts the value changes of every signal once the
+    /// whole dump is parsed. When a signal changes more than once at the
+    /// same time, the change that comes last in the file wins.
+    #[default]
+    Sort,
+    /// Silently drops the value changes of every block whose timestamp is
+    /// earlier than the largest timestamp seen so far.
+    Drop,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            threads: 1,
+            progress: None,
+            cancellation: None,
+            signal_filter: None,
+            lenient: false,
+            timestamp_policy: TimestampPolicy::Sort,
+            assemble_buses: false,
+            split_hierarchical_names: false,
+        }
+    }
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("threads", &self.threads)
+            .field("progress", &self.progress.as_ref().map(|_| ".."))
+            .field("cancellation", &self.cancellation)
+            .field("signal_filter", &self.signal_filter)
+            .field("lenient", &self.lenient)
+            .field("timestamp_policy", &self.timestamp_policy)
+            .field("assemble_buses", &self.assemble_buses)
+            .field("split_hierarchical_names", &self.split_hierarchical_names)
+            .finish()
+    }
+}
+
+impl ParseOptions {
+    pub(super) fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => std::thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+            threads => threads,
+        }
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+
+/// Parses the value change section of an in memory VCD on several threads.
+/// The section is split into chunks that each begin with a `#timestamp`
+/// word, every chunk is parsed into its own set of signal
+/// buffers, and the chunks are then stitched back together in file order
+/// so that the resulting ``VCD`` is identical to the one built by the
+/// sequential parser.
+use num::BigUint;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+
+use super::super::error::{Diagnostic, ParseError};
+use super::super::reader::SliceWordReader;
+use super::super::signal::{LsbIdxOfTmstmpValOnTmln, SignalEnum};
+use super::super::types::{Metadata, SignalIdx, VCD};
+
+use super::events::{finish_events, parse_events, sort_value_changes, EventParserState};
+use super::options::TimestampPolicy;
+use super::progress::ProgressMonitor;
+
+pub(super) fn parse_events_parallel(
+    word_reader: &SliceWordReader<'_>,
+    vcd: &mut VCD,
+    signal_map: &HashMap<String, SignalIdx>,
+    threads: usize,
+    state: &EventParserState,
+    monitor: &ProgressMonitor,
+) -> Result<(), ParseError> {
+    let body_start = word_reader.position().offset;
+    let body = &word_reader.bytes()[body_start..];
+    let chunk_starts: Vec<usize> = chunk_starts(body, threads)
+        .into_iter()
+        .map(|start| body_start + start)
+        .collect();
+    parse_chunks(word_reader, vcd, signal_map, &chunk_starts, state, monitor)
+}
+
+/// Parses the value change section that follows ``word_reader``'s current
+/// position in chunks, one thread per chunk. ``chunk_starts`` are offsets
+/// into ``word_reader``'s buffer; the first must be its current position
+/// and all others must be the offsets of `#timestamp` words. Every chunk
+/// is parsed starting out from a copy of ``state``.
+fn parse_chunks(
+    word_reader: &SliceWordReader<'_>,
+    vcd: &mut VCD,
+    signal_map: &HashMap<String, SignalIdx>,
+    chunk_starts: &[usize],
+    state: &EventParserState,
+    monitor: &ProgressMonitor,
+) -> Result<(), ParseError> {
+    // Each chunk's reader has to pick up with the same line counter the
+    // sequential reader would have had at that point, so that cursors in
+    // error messages come out the same.
+    let body_end = word_reader.bytes().len();
+    let mut chunks = vec![];
+    let mut reader = word_reader.slice(word_reader.position().offset, body_end);
+    for (chunk_idx, chunk_start) in chunk_starts.iter().enumerate() {
+        reader = reader.slice(*chunk_start, body_end);
+        let chunk_end = chunk_starts.get(chunk_idx + 1).copied().unwrap_or(body_end);
+        chunks.push(reader.slice(*chunk_start, chunk_end));
+    }
+
+    let consumed_before = monitor.bytes_consumed();
+    let shells: Vec<VCD> = chunks.iter().map(|_| empty_copy(vcd)).collect();
+    type ParsedChunk = Result<(VCD, EventParserState), ParseError>;
+    let parsed_chunks: Vec<ParsedChunk> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .zip(shells)
+            .map(|(mut chunk_reader, mut chunk_vcd)| {
+                let mut chunk_state = state.clone();
+                scope.spawn(move || {
+                    parse_events(
+                        &mut chunk_reader,
+                        &mut chunk_vcd,
+                        signal_map,
+                        &mut chunk_state,
+                        monitor,
+                    )?;
+                    Ok((chunk_vcd, chunk_state))
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    // Whether a timestamp is dropped, or shares its entry on the timeline
+    // with the one before it, depends on the timestamps before it. Chunks
+    // are parsed without knowing those, so when a chunk starts with a
+    // timestamp that isn't larger than every timestamp before it, we fall
+    // back to parsing the whole section sequentially. What the chunks
+    // parsed, their diagnostics included, is thrown away with them.
+    let mut out_of_order = false;
+    let mut largest: Option<&BigUint> = None;
+    let mut last: Option<&BigUint> = None;
+    for (chunk_idx, parsed_chunk) in parsed_chunks.iter().enumerate() {
+        let Ok((chunk_vcd, chunk_state)) = parsed_chunk else {
+            break;
+        };
+        let first = timestamp_at(word_reader.bytes(), chunk_starts[chunk_idx]);
+        if let (Some(first), Some(largest)) = (&first, largest) {
+            let sequential = match state.timestamp_policy {
+                TimestampPolicy::Reject | TimestampPolicy::Drop => first <= largest,
+                TimestampPolicy::Sort => Some(first) == last,
+            };
+            if sequential {
+                let mut word_reader = word_reader.slice(chunk_starts[0], body_end);
+                let mut state = state.clone();
+                monitor.rewind(consumed_before);
+                parse_events(&mut word_reader, vcd, signal_map, &mut state, monitor)?;
+                finish_events(vcd, &state);
+                return Ok(());
+            }
+            out_of_order |= first < largest;
+        }
+
+        out_of_order |= chunk_state.out_of_order;
+        largest = largest.max(chunk_vcd.largest_timestamp.as_ref());
+        last = chunk_state.curr_tmstmp.as_ref().or(last);
+    }
+
+    // the sequential parser would have stopped at the first error in the
+    // file, which is the error of the earliest failing chunk
+    for parsed_chunk in parsed_chunks {
+        let (chunk_vcd, _) = parsed_chunk?;
+        append_chunk(vcd, chunk_vcd)?;
+    }
+    if out_of_order {
+        sort_value_changes(vcd);
+    }
+
+    Ok(())
+}
+
+/// The value of the `#timestamp` word at ``offset`` in ``bytes``, if there
+/// is one.
+fn timestamp_at(bytes: &[u8], offset: usize) -> Option<BigUint> {
+    let word = bytes[offset..].strip_prefix(b"#")?;
+    let len = word
+        .iter()
+        .position(|chr| chr.is_ascii_whitespace())
+        .unwrap_or(word.len());
+    BigUint::parse_bytes(&word[..len], 10)
+}
+
+/// Picks up to ``threads`` offsets into ``body`` at which a chunk may start.
+/// The first chunk always starts at 0, every other chunk starts on a
+/// `#timestamp` word that is not part of a comment.
+fn chunk_starts(body: &[u8], threads: usize) -> Vec<usize> {
+    let mut starts = vec![0];
+    let target_len = body.len() / threads.max(1);
+    if target_len == 0 {
+        return starts;
+    }
+
+    for chunk_idx in 1..threads {
+        let prev_start = starts[starts.len() - 1];
+        let mut search_from = (chunk_idx * target_len).max(prev_start + 1);
+
+        loop {
+            if search_from >= body.len() {
+                return starts;
+            }
+
+            let found = body[search_from - 1..]
+                .windows(2)
+                .position(|window| window[0].is_ascii_whitespace() && window[1] == b'#');
+            let candidate = match found {
+                Some(offset) => search_from + offset,
+                None => return starts,
+            };
+
+            if !is_timestamp(body, candidate) || in_comment(body, prev_start, candidate) {
+                search_from = candidate + 1;
+            } else {
+                starts.push(candidate);
+                break;
+            }
+        }
+    }
+
+    starts
+}
+
+/// Whether the word starting at ``idx`` is a timestamp. Identifier codes
+/// may start with `#` too, so we also make sure the word isn't the
+/// identifier of a preceding vector, real or string value change.
+fn is_timestamp(body: &[u8], idx: usize) -> bool {
+    let word_len = body[idx..]
+        .iter()
+        .position(|chr| chr.is_ascii_whitespace())
+        .unwrap_or(body.len() - idx);
+    let word = &body[idx..idx + word_len];
+    if word.len() < 2 || !word[1..].iter().all(u8::is_ascii_digit) {
+        return false;
+    }
+
+    let prev_word_end = match body[..idx]
+        .iter()
+        .rposition(|chr| !chr.is_ascii_whitespace())
+    {
+        Some(prev_word_end) => prev_word_end,
+        None => return true,
+    };
+    let prev_word_start = body[..prev_word_end]
+        .iter()
+        .rposition(|chr| chr.is_ascii_whitespace())
+        .map_or(0, |idx| idx + 1);
+    !matches!(
+        body[prev_word_start],
+        b'b' | b'B' | b'r' | b'R' | b's' | b'S'
+    )
+}
+
+/// Whether ``idx`` lies within a `$comment ... $end` block, given that
+/// ``known_outside`` is an earlier offset known not to. We only need to
+/// find the closest keyword that opens or closes a comment before ``idx``.
+fn in_comment(body: &[u8], known_outside: usize, idx: usize) -> bool {
+    for keyword_start in (known_outside..idx).rev() {
+        let starts_word = keyword_start == 0 || body[keyword_start - 1].is_ascii_whitespace();
+        if body[keyword_start] != b'$' || !starts_word {
+            continue;
+        }
+
+        let keyword_len = body[keyword_start..]
+            .iter()
+            .position(|chr| chr.is_ascii_whitespace())
+            .unwrap_or(body.len() - keyword_start);
+        match &body[keyword_start..keyword_start + keyword_len] {
+            b"$end" => return false,
+            b"$comment" => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// A VCD that shares ``vcd``'s signal declarations, but has not recorded
+/// any value changes.
+pub(super) fn empty_copy(vcd: &VCD) -> VCD {
+    VCD {
+        metadata: Metadata {
+            date: None,
+            raw_date: None,
+            version: None,
+            timescale: None,
+            timezero: None,
+            extra_sections: vec![],
+        },
+        tmstmps_encoded_as_u8s: vec![],
+        all_signals: vcd
+            .all_signals
+            .iter()
+            .map(SignalEnum::without_values)
+            .collect(),
+        all_scopes: vec![],
+        root_scopes: vec![],
+        largest_timestamp: None,
+        dump_commands: vec![],
+        comments: vec![],
+        initial_values: vec![],
+        diagnostics: vec![],
+        lazy: None,
+    }
+}
+
+/// Appends the value changes recorded in ``chunk`` to ``vcd``, shifting the
+/// chunk's timestamp indices past the timestamps already in ``vcd``.
+pub(super) fn append_chunk(vcd: &mut VCD, chunk: VCD) -> Result<(), ParseError> {
+    let VCD {
+        tmstmps_encoded_as_u8s: mut chunk_tmstmps,
+        all_signals: chunk_signals,
+        largest_timestamp: chunk_largest_timestamp,
+        dump_commands: mut chunk_dump_commands,
+        comments: mut chunk_comments,
+        initial_values: chunk_initial_values,
+        diagnostics: chunk_diagnostics,
+        ..
+    } = chunk;
+
+    let tmstmp_offset = u32::try_from(vcd.tmstmps_encoded_as_u8s.len())
+        .map_err(|_| ParseError::limit("the timeline exceeds 2^32 - 1 bytes", None))?;
+    let shift = |indxs: Vec<LsbIdxOfTmstmpValOnTmln>| {
+        indxs
+            .into_iter()
+            .map(|LsbIdxOfTmstmpValOnTmln(idx)| {
+                idx.checked_add(tmstmp_offset)
+                    .map(LsbIdxOfTmstmpValOnTmln)
+                    .ok_or_else(|| ParseError::limit("the timeline exceeds 2^32 - 1 bytes", None))
+            })
+            .collect::<Result<Vec<_>, ParseError>>()
+    };
+
+    vcd.dump_commands.append(&mut chunk_dump_commands);
+    vcd.comments.append(&mut chunk_comments);
+    // like the value changes themselves, initial values of signals that
+    // had an error before this chunk are dropped
+    for (signal_idx, value) in chunk_initial_values {
+        if let SignalEnum::Data {
+            signal_error: None, ..
+        } = vcd.dealiasing_signal_idx_to_signal_lookup_mut(&signal_idx)?
+        {
+            vcd.initial_values.push((signal_idx, value));
+        }
+    }
+
+    // each distinct problem is only reported the first time it comes up,
+    // which may have been in an earlier chunk
+    let mut reported: HashSet<String> = vcd.diagnostics.iter().map(Diagnostic::message).collect();
+    for diagnostic in chunk_diagnostics {
+        if reported.insert(diagnostic.message()) {
+            vcd.diagnostics.push(diagnostic);
+        }
+    }
+
+    vcd.tmstmps_encoded_as_u8s.append(&mut chunk_tmstmps);
+    if chunk_largest_timestamp > vcd.largest_timestamp {
+        vcd.largest_timestamp = chunk_largest_timestamp;
+    }
+
+    for (signal, chunk_signal) in vcd.all_signals.iter_mut().zip(chunk_signals) {
+        if let (
+            SignalEnum::Data {
+                signal_error,
+                nums_encoded_as_fixed_width_le_u8,
+                string_vals,
+                lsb_indxs_of_num_tmstmp_vals_on_tmln,
+                byte_len_of_num_tmstmp_vals_on_tmln,
+                byte_len_of_string_tmstmp_vals_on_tmln,
+                lsb_indxs_of_string_tmstmp_vals_on_tmln,
+                ..
+            },
+            SignalEnum::Data {
+                signal_error: chunk_signal_error,
+                nums_encoded_as_fixed_width_le_u8: mut chunk_nums,
+                string_vals: mut chunk_string_vals,
+                lsb_indxs_of_num_tmstmp_vals_on_tmln: chunk_lsb_indxs_of_num,
+                byte_len_of_num_tmstmp_vals_on_tmln: mut chunk_byte_len_of_num,
+                byte_len_of_string_tmstmp_vals_on_tmln: mut chunk_byte_len_of_string,
+                lsb_indxs_of_string_tmstmp_vals_on_tmln: chunk_lsb_indxs_of_string,
+                ..
+            },
+        ) = (signal, chunk_signal)
+        {
+            // the sequential parser ignores every value change of a signal
+            // after it has found an error in that signal
+            if signal_error.is_some() {
+                continue;
+            }
+
+            nums_encoded_as_fixed_width_le_u8.append(&mut chunk_nums);
+            lsb_indxs_of_num_tmstmp_vals_on_tmln.append(&mut shift(chunk_lsb_indxs_of_num)?);
+            byte_len_of_num_tmstmp_vals_on_tmln.append(&mut chunk_byte_len_of_num);
+
+            string_vals.append(&mut chunk_string_vals);
+            lsb_indxs_of_string_tmstmp_vals_on_tmln.append(&mut shift(chunk_lsb_indxs_of_string)?);
+            byte_len_of_string_tmstmp_vals_on_tmln.append(&mut chunk_byte_len_of_string);
+
+            *signal_error = chunk_signal_error;
+        }
+    }
+
+    Ok(())
+}
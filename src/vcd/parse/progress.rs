@@ -0,0 +1,127 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use num::BigUint;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::super::error::ParseError;
+use super::super::types::VCD;
+use super::options::ParseOptions;
+
+/// How often, in bytes of input, the progress callback is invoked and the
+/// cancellation token is checked.
+pub(super) const REPORT_INTERVAL: usize = 1 << 20;
+
+/// A snapshot of how far a parse has got, handed to the progress callback
+/// in ``ParseOptions``.
+#[derive(Debug, Clone)]
+pub struct ParseProgress {
+    /// Bytes of (decompressed) input parsed so far.
+    pub bytes_consumed: usize,
+    /// The size of the input, if it is known up front. It isn't for
+    /// streams and compressed files.
+    pub total_bytes: Option<usize>,
+    /// The most recent timestamp parsed. When parsing on several threads,
+    /// this is the most recent timestamp of whichever thread reported.
+    pub current_timestamp: Option<BigUint>,
+    /// The number of signals declared in the header.
+    pub signals_declared: usize,
+}
+
+pub type ProgressCallback = Arc<dyn Fn(&ParseProgress) + Send + Sync>;
+
+/// Lets another thread abort a parse that is in progress. Clones share the
+/// same flag, so a UI can keep one clone and hand another to the parser.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Adds up the bytes consumed by every thread taking part in a parse and
+/// relays them to the caller's progress callback.
+pub(super) struct ProgressMonitor<'a> {
+    progress: Option<&'a ProgressCallback>,
+    cancellation: Option<&'a CancellationToken>,
+    total_bytes: Option<usize>,
+    bytes_consumed: AtomicUsize,
+    /// The most bytes consumed the callback has been told about.
+    reported: AtomicUsize,
+}
+
+impl<'a> ProgressMonitor<'a> {
+    pub(super) fn new(options: &'a ParseOptions, total_bytes: Option<usize>) -> Self {
+        ProgressMonitor {
+            progress: options.progress.as_ref(),
+            cancellation: options.cancellation.as_ref(),
+            total_bytes,
+            bytes_consumed: AtomicUsize::new(0),
+            reported: AtomicUsize::new(0),
+        }
+    }
+
+    /// A monitor that neither reports progress nor can be cancelled.
+    pub(super) fn disabled() -> Self {
+        ProgressMonitor {
+            progress: None,
+            cancellation: None,
+            total_bytes: None,
+            bytes_consumed: AtomicUsize::new(0),
+            reported: AtomicUsize::new(0),
+        }
+    }
+
+    pub(super) fn bytes_consumed(&self) -> usize {
+        self.bytes_consumed.load(Ordering::Relaxed)
+    }
+
+    /// Goes back to ``bytes_consumed``, for input that is about to be
+    /// parsed again. The callback isn't called again until the parse gets
+    /// past where it had got to, so the progress it sees never goes back.
+    pub(super) fn rewind(&self, bytes_consumed: usize) {
+        self.bytes_consumed.store(bytes_consumed, Ordering::Relaxed);
+    }
+
+    /// Records that ``newly_consumed`` more bytes have been parsed into
+    /// ``vcd``. Fails if the parse has been cancelled.
+    pub(super) fn report(&self, newly_consumed: usize, vcd: &VCD) -> Result<(), ParseError> {
+        if self
+            .cancellation
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(ParseError::Cancelled);
+        }
+
+        let bytes_consumed = self
+            .bytes_consumed
+            .fetch_add(newly_consumed, Ordering::Relaxed)
+            + newly_consumed;
+        let reported = self.reported.fetch_max(bytes_consumed, Ordering::Relaxed);
+        if bytes_consumed < reported {
+            return Ok(());
+        }
+        if let Some(progress) = self.progress {
+            progress(&ParseProgress {
+                bytes_consumed,
+                total_bytes: self.total_bytes,
+                current_timestamp: vcd.largest_timestamp.clone(),
+                signals_declared: vcd.all_signals.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::collections::HashSet;
+
+use super::super::error::{Diagnostic, ParseError, Severity};
+use super::super::types::VCD;
+
+/// Decides whether a problem in the input aborts the parse, or is recorded
+/// in ``VCD::diagnostics`` so that the caller can skip or repair the
+/// offending construct and carry on.
+#[derive(Debug, Default, Clone)]
+pub(super) struct Recovery {
+    lenient: bool,
+    /// Whether to also report what parsing tolerates, such as repeated
+    /// timestamps, and every occurrence of each problem. Set when
+    /// validating a VCD.
+    pedantic: bool,
+    /// The messages of the problems ``recover_once`` has recorded so far.
+    reported: HashSet<String>,
+}
+
+impl Recovery {
+    pub(super) fn new(lenient: bool) -> Self {
+        Recovery {
+            lenient,
+            pedantic: false,
+            reported: HashSet::new(),
+        }
+    }
+
+    /// A lenient recovery that reports everything ``validate_vcd`` checks.
+    pub(super) fn pedantic() -> Self {
+        Recovery {
+            pedantic: true,
+            ..Recovery::new(true)
+        }
+    }
+
+    pub(super) fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    pub(super) fn is_pedantic(&self) -> bool {
+        self.pedantic
+    }
+
+    /// Hands back ``error`` when parsing strictly, or records it and lets
+    /// the caller recover when parsing leniently.
+    pub(super) fn recover(
+        &mut self,
+        vcd: &mut VCD,
+        severity: Severity,
+        error: ParseError,
+    ) -> Result<(), ParseError> {
+        if !self.lenient {
+            return Err(error);
+        }
+
+        vcd.diagnostics.push(Diagnostic { severity, error });
+        Ok(())
+    }
+
+    /// Like ``recover``, but only records the first occurrence of each
+    /// problem unless pedantic, so that a single undeclared identifier that
+    /// changes value at every timestamp doesn't flood the diagnostics.
+    pub(super) fn recover_once(
+        &mut self,
+        vcd: &mut VCD,
+        severity: Severity,
+        error: ParseError,
+    ) -> Result<(), ParseError> {
+        if !self.lenient {
+            return Err(error);
+        }
+
+        let diagnostic = Diagnostic { severity, error };
+        if self.pedantic || self.reported.insert(diagnostic.message()) {
+            vcd.diagnostics.push(diagnostic);
+        }
+        Ok(())
+    }
+
+    /// Records ``error`` like ``recover_once`` when parsing leniently, but
+    /// never fails. For problems that only spoil a single signal, which
+    /// strict parses merely mark the signal with.
+    pub(super) fn note_once(&mut self, vcd: &mut VCD, severity: Severity, error: ParseError) {
+        if self.lenient {
+            let _ = self.recover_once(vcd, severity, error);
+        }
+    }
+
+    /// Records the error made by ``error`` when pedantic. For departures
+    /// from the standard that parsing otherwise tolerates.
+    pub(super) fn nitpick(
+        &mut self,
+        vcd: &mut VCD,
+        severity: Severity,
+        error: impl FnOnce() -> ParseError,
+    ) {
+        if self.pedantic {
+            vcd.diagnostics.push(Diagnostic {
+                severity,
+                error: error(),
+            });
+        }
+    }
+}
@@ -7,115 +7,98 @@
 /// building the resulting signal tree
 use std::collections::HashMap;
 
-use super::super::reader::{curr_word, next_word, WordReader};
-use super::super::signal::{SignalEnum, SignalType};
-use super::super::types::{Scope, ScopeIdx, SignalIdx, VCD};
+use super::super::error::{ParseError, Severity};
+use super::super::reader::{curr_word, next_word, WordSource};
+use super::super::signal::{BitIndex, SignalEnum, SignalType};
+use super::super::types::{HeaderSection, Scope, ScopeIdx, ScopeType, SignalIdx, VCD};
 
 use super::combinator_atoms::{ident, tag};
+use super::metadata::parse_section_text;
+use super::recovery::Recovery;
 use super::types::ParseResult;
 
-pub(super) fn parse_var<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
+pub(super) fn parse_var<W: WordSource>(
+    word_reader: &mut W,
     parent_scope_idx: ScopeIdx,
     vcd: &mut VCD,
     signal_map: &mut HashMap<String, SignalIdx>,
-    path: &Vec<String>,
-) -> Result<(), String> {
+    path: &[String],
+    recovery: &mut Recovery,
+) -> Result<(), ParseError> {
     let (word, cursor) = next_word!(word_reader)?;
-    let expected_types = [
-        "event",
-        "integer",
-        "parameter",
-        "real",
-        "realtime",
-        "reg",
-        "string",
-        "supply0",
-        "supply1",
-        "time",
-        "tri",
-        "triand",
-        "trior",
-        "trireg",
-        "tri0",
-        "tri1",
-        "wand",
-        "wire",
-        "wor",
-    ];
-
     // $var parameter 3 a IDLE $end
     //      ^^^^^^^^^ - var_type
-    let var_type = match word {
-        "event" => Ok(SignalType::Event),
-        "integer" => Ok(SignalType::Integer),
-        "parameter" => Ok(SignalType::Parameter),
-        "real" => Ok(SignalType::Real),
-        "realtime" => Ok(SignalType::RealTime),
-        "reg" => Ok(SignalType::Reg),
-        "string" => Ok(SignalType::Str),
-        "supply0" => Ok(SignalType::Supply0),
-        "supply1" => Ok(SignalType::Supply1),
-        "tri" => Ok(SignalType::Tri),
-        "triand" => Ok(SignalType::TriAnd),
-        "trior" => Ok(SignalType::TriOr),
-        "trireg" => Ok(SignalType::TriReg),
-        "tri0" => Ok(SignalType::Tri0),
-        "tri1" => Ok(SignalType::Tri1),
-        "time" => Ok(SignalType::Time),
-        "wand" => Ok(SignalType::WAnd),
-        "wire" => Ok(SignalType::Wire),
-        "wor" => Ok(SignalType::WOr),
-        _ => {
-            let err = format!(
-                "Error near {}:{} \
-                               found keyword `{word}` but expected one of \
-                               {expected_types:?} on {cursor:?}",
-                file!(),
-                line!()
-            );
-            Err(err)
-        }
-    }?;
+    if word.starts_with('$') {
+        // the type is missing altogether, so we skip the variable
+        let at_end = word == "$end";
+        let error = ParseError::UnknownKeyword {
+            keyword: word.to_string(),
+            expected: "a variable type".to_string(),
+            cursor,
+        };
+        recovery.recover(vcd, Severity::Error, error)?;
+        return skip_declaration(word_reader, at_end);
+    }
+    let var_type = SignalType::from_keyword(word);
+    if let (SignalType::Other(keyword), true) = (&var_type, recovery.is_lenient()) {
+        // variables of types we don't know of are parsed all the same, and
+        // only noted when we've been asked to look out for problems
+        let error = ParseError::UnknownKeyword {
+            keyword: keyword.clone(),
+            expected: "a variable type such as `wire`, `reg`, `integer` or `logic`".to_string(),
+            cursor,
+        };
+        recovery.recover(vcd, Severity::Warning, error)?;
+    }
 
-    let (word, cursor) = next_word!(word_reader)?;
+    let (word, width_cursor) = next_word!(word_reader)?;
 
-    let parse_err = format!("failed to parse as usize on {cursor:?}");
+    // the ports of extended VCDs may give their width as a range
+    // $var port [3:0] <1 data $end
+    //           ^^^^^ - size
+    let mut size = None;
 
     // $var parameter 3 a IDLE $end
     //                ^ - num_bits
     let num_bits = match var_type {
-        SignalType::Event
-        | SignalType::Integer
-        | SignalType::Parameter
-        | SignalType::Reg
-        | SignalType::Supply0
-        | SignalType::Supply1
-        | SignalType::Tri
-        | SignalType::TriAnd
-        | SignalType::TriOr
-        | SignalType::TriReg
-        | SignalType::Tri0
-        | SignalType::Tri1
-        | SignalType::Time
-        | SignalType::WAnd
-        | SignalType::Wire
-        | SignalType::WOr => {
-            let num_bits = word
-                .parse::<usize>()
-                .unwrap_or_else(|_| panic!("{}", parse_err));
+        SignalType::Port if word.starts_with('[') => match BitIndex::parse(word) {
+            Some(BitIndex::Range(range)) => {
+                let num_bits = range.width();
+                size = Some(word.to_string());
+                Some(num_bits)
+            }
+            _ => {
+                let error = ParseError::BadWidth {
+                    width: word.to_string(),
+                    cursor: width_cursor,
+                };
+                recovery.recover(vcd, Severity::Error, error)?;
+                return skip_declaration(word_reader, false);
+            }
+        },
+        // for strings, reals, and realtimes we don't really care what the number of bits is
+        SignalType::Str | SignalType::Real | SignalType::RealTime | SignalType::ShortReal => None,
+        _ => {
+            let num_bits = match word.parse::<usize>() {
+                Ok(num_bits) => num_bits,
+                Err(_) => {
+                    let at_end = word == "$end";
+                    let error = ParseError::BadWidth {
+                        width: word.to_string(),
+                        cursor: width_cursor,
+                    };
+                    recovery.recover(vcd, Severity::Error, error)?;
+                    return skip_declaration(word_reader, at_end);
+                }
+            };
             let num_bits = u32::try_from(num_bits).map_err(|_| {
-                format!(
-                    "Error near {}:{} while parsing vcd file at {cursor:?}. \
-                     This signal has {num_bits} > 2^32 - 1 bits.",
-                    file!(),
-                    line!()
+                ParseError::limit(
+                    format!("signal has {num_bits} > 2^32 - 1 bits"),
+                    Some(width_cursor),
                 )
             })?;
             Some(num_bits)
         }
-        // for strings, reals, and realtimes we don't really care what the number of bits is
-        _ => None,
     };
 
     // $var parameter 3 a IDLE $end
@@ -126,7 +109,6 @@ pub(super) fn parse_var<R: std::io::Read>(
     // $var parameter 3 a IDLE $end
     //                    ^^^^ - full_signal_name(can extend until $end)
     let mut full_signal_name = Vec::<String>::new();
-    let mut size = None;
     loop {
         let (word, _) = next_word!(word_reader)?;
         match word {
@@ -137,11 +119,15 @@ pub(super) fn parse_var<R: std::io::Read>(
     }
     let full_signal_name = full_signal_name.join(" ");
 
-    let num_bytes = if num_bits.is_some() {
-        let bytes_required = SignalEnum::bytes_required(num_bits.unwrap(), &full_signal_name)?;
-        Some(bytes_required)
-    } else {
-        None
+    // reals are kept as the bytes of an f64, whatever their declared width
+    let num_bytes = match num_bits {
+        _ if var_type.is_real() => Some(8),
+        Some(num_bits) => Some(SignalEnum::bytes_required(
+            num_bits,
+            &full_signal_name,
+            width_cursor,
+        )?),
+        None => None,
     };
 
     // Is the current variable an alias to a signal already encountered?
@@ -157,6 +143,7 @@ pub(super) fn parse_var<R: std::io::Read>(
                     .cloned()
                     .chain([full_signal_name])
                     .collect::<Vec<String>>(),
+                index: size,
                 signal_alias: *ref_signal_idx,
             };
             (signal, signal_idx)
@@ -174,6 +161,7 @@ pub(super) fn parse_var<R: std::io::Read>(
                 signal_type: var_type,
                 index: size,
                 signal_error: None,
+                loaded: true,
                 num_bits,
                 num_bytes,
                 self_idx: signal_idx,
@@ -198,11 +186,12 @@ pub(super) fn parse_var<R: std::io::Read>(
 
 /// Sometimes, variables can be listed outside of scopes.
 /// We call these orphaned vars.
-fn parse_orphaned_vars<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
+fn parse_orphaned_vars<W: WordSource>(
+    word_reader: &mut W,
     vcd: &mut VCD,
     signal_map: &mut HashMap<String, SignalIdx>,
-) -> Result<(), String> {
+    recovery: &mut Recovery,
+) -> Result<(), ParseError> {
     // create scope for unscoped signals if such a scope does not
     // yet exist
     let scope_name = "Orphaned Signals";
@@ -226,34 +215,39 @@ fn parse_orphaned_vars<R: std::io::Read>(
     if !scope_already_exists {
         vcd.all_scopes.push(Scope {
             name: scope_name.to_string(),
+            scope_type: ScopeType::Module,
             self_idx: scope_idx,
             child_signals: vec![],
             child_scopes: vec![],
+            comments: vec![],
         });
         vcd.root_scopes.push(scope_idx);
     }
 
     // we can go ahead and parse the current var as we've already encountered
     // "$var" before now.
-    parse_var(word_reader, scope_idx, vcd, signal_map, &vec![])?;
+    parse_var(word_reader, scope_idx, vcd, signal_map, &[], recovery)?;
 
     loop {
         let (word, cursor) = next_word!(word_reader)?;
 
         match word {
             "$var" => {
-                parse_var(word_reader, scope_idx, vcd, signal_map, &vec![])?;
+                parse_var(word_reader, scope_idx, vcd, signal_map, &[], recovery)?;
             }
             "$scope" => break,
+            "$comment" => {
+                let comment = parse_section_text(word_reader)?;
+                let ScopeIdx(idx) = scope_idx;
+                vcd.all_scopes[idx].comments.push(comment);
+            }
             _ => {
-                let msg = format!(
-                    "Error near {}:{}.\
-                          Expected $scope or $var, found \
-                          {word} at {cursor:?}",
-                    file!(),
-                    line!()
-                );
-                Err(msg)?;
+                let error = ParseError::UnknownKeyword {
+                    keyword: word.to_string(),
+                    expected: "`$scope`, `$var` or `$comment`".to_string(),
+                    cursor,
+                };
+                recovery.recover(vcd, Severity::Error, error)?;
             }
         };
     }
@@ -261,30 +255,33 @@ fn parse_orphaned_vars<R: std::io::Read>(
     Ok(())
 }
 
-fn parse_scopes_inner<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
+/// Parses a scope and everything nested in it. Returns whether
+/// `$enddefinitions` was reached before the scope was closed, which is only
+/// tolerated when parsing leniently.
+fn parse_scopes_inner<W: WordSource>(
+    word_reader: &mut W,
     parent_scope_idx: Option<ScopeIdx>,
     vcd: &mut VCD,
     signal_map: &mut HashMap<String, SignalIdx>,
-    path: &Vec<String>,
-) -> Result<(), String> {
+    path: &[String],
+    recovery: &mut Recovery,
+) -> Result<bool, ParseError> {
     // $scope module reg_mag_i $end
     //        ^^^^^^ - module keyword
     let (keyword, cursor) = next_word!(word_reader)?;
 
-    let expected = ["module", "begin", "task", "function", "fork"];
-    if expected.contains(&keyword) {
-        Ok(())
-    } else {
-        let err = format!(
-            "Error near {}:{}. \
-                            found keyword `{keyword}` but expected one of \
-                            {expected:?} on {cursor:?}",
-            file!(),
-            line!()
-        );
-        Err(err)
-    }?;
+    let scope_type = ScopeType::from_keyword(keyword);
+    if let (ScopeType::Other(keyword), true) = (&scope_type, recovery.is_lenient()) {
+        // scopes of kinds we don't know of are parsed all the same, and
+        // only noted when we've been asked to look out for problems
+        let error = ParseError::UnknownKeyword {
+            keyword: keyword.clone(),
+            expected: "a scope type such as `module`, `begin`, `task`, `function` or `fork`"
+                .to_string(),
+            cursor,
+        };
+        recovery.recover(vcd, Severity::Warning, error)?;
+    }
 
     // $scope module reg_mag_i $end
     //               ^^^^^^^^^ - scope name
@@ -293,7 +290,7 @@ fn parse_scopes_inner<R: std::io::Read>(
     // since these occur in the wild, we'll tolerate them even if it is unclear
     // if it is supported or not by the spec.
     if scope_name != "$end" {
-        let mut path = path.clone();
+        let mut path = path.to_vec();
         path.push(scope_name.to_string());
 
         let curr_scope_idx = ScopeIdx(vcd.all_scopes.len());
@@ -312,9 +309,11 @@ fn parse_scopes_inner<R: std::io::Read>(
         // add this scope to list of existing scopes
         vcd.all_scopes.push(Scope {
             name: scope_name.to_string(),
+            scope_type,
             self_idx: curr_scope_idx,
             child_signals: vec![],
             child_scopes: vec![],
+            comments: vec![],
         });
 
         // $scope module reg_mag_i $end
@@ -330,49 +329,63 @@ fn parse_scopes_inner<R: std::io::Read>(
                     match residual {
                         "scope" => {
                             // recursive - parse inside of current scope tree
-                            parse_scopes_inner(
+                            if parse_scopes_inner(
                                 word_reader,
                                 Some(curr_scope_idx),
                                 vcd,
                                 signal_map,
                                 &path,
-                            )?;
+                                recovery,
+                            )? {
+                                return Ok(true);
+                            }
                         }
                         "var" => {
-                            parse_var(word_reader, curr_scope_idx, vcd, signal_map, &path)?;
+                            parse_var(
+                                word_reader,
+                                curr_scope_idx,
+                                vcd,
+                                signal_map,
+                                &path,
+                                recovery,
+                            )?;
                         }
                         "upscope" => {
                             ident(word_reader, "$end")?;
                             break;
                         }
-                        // we ignore comments
-                        "comment" => loop {
-                            if ident(word_reader, "$end").is_ok() {
-                                break;
-                            }
-                        },
+                        "comment" => {
+                            let comment = parse_section_text(word_reader)?;
+                            let ScopeIdx(idx) = curr_scope_idx;
+                            vcd.all_scopes[idx].comments.push(comment);
+                        }
                         _ => {
-                            let err = format!(
-                                "Error near {}:{}. \
-                                               found keyword `{residual}` but expected \
-                                               `$scope`, `$var`, `$comment`, or `$upscope` \
-                                               on {cursor:?}",
-                                file!(),
-                                line!()
-                            );
-                            return Err(err);
+                            let at_end = word == "$end";
+                            let error = ParseError::UnknownKeyword {
+                                keyword: word.to_string(),
+                                expected: "`$scope`, `$var`, `$comment`, or `$upscope`".to_string(),
+                                cursor,
+                            };
+                            if word == "$enddefinitions" {
+                                // the scope was never closed, so we close it
+                                // along with all of its parents
+                                recovery.recover(vcd, Severity::Warning, error)?;
+                                ident(word_reader, "$end")?;
+                                return Ok(true);
+                            }
+                            recovery.recover(vcd, Severity::Error, error)?;
+                            skip_declaration(word_reader, at_end)?;
                         }
                     }
                 }
                 _ => {
-                    let err = format!(
-                        "Error near {}:{}. \
-                                      found keyword `{matched}` but \
-                                      expected `$` on {cursor:?}",
-                        file!(),
-                        line!()
-                    );
-                    return Err(err);
+                    // a stray word, which we skip by itself
+                    let error = ParseError::UnknownKeyword {
+                        keyword: word.to_string(),
+                        expected: "a keyword starting with `$`".to_string(),
+                        cursor,
+                    };
+                    recovery.recover(vcd, Severity::Error, error)?;
                 }
             }
         }
@@ -396,65 +409,82 @@ fn parse_scopes_inner<R: std::io::Read>(
                     match residual {
                         "scope" => {
                             // recursive - parse inside of current scope tree
-                            parse_scopes_inner(
+                            if parse_scopes_inner(
                                 word_reader,
                                 parent_scope_idx,
                                 vcd,
                                 signal_map,
-                                &path,
-                            )?;
+                                path,
+                                recovery,
+                            )? {
+                                return Ok(true);
+                            }
                         }
                         "upscope" => {
                             ident(word_reader, "$end")?;
                             break;
                         }
-                        // we ignore comments
-                        "comment" => loop {
-                            if ident(word_reader, "$end").is_ok() {
-                                break;
+                        // nameless scopes are transparent, so their comments
+                        // belong to whatever encloses them
+                        "comment" => {
+                            let comment = parse_section_text(word_reader)?;
+                            match parent_scope_idx {
+                                Some(ScopeIdx(idx)) => vcd.all_scopes[idx].comments.push(comment),
+                                None => vcd
+                                    .metadata
+                                    .extra_sections
+                                    .push(HeaderSection::Comment(comment)),
                             }
-                        },
+                        }
                         _ => {
-                            let err = format!(
-                                "Error near {}:{}. \
-                                               found keyword `{residual}` in annonyoums scope but expected \
-                                               `$scope`, `$comment`, or `$upscope` \
-                                               on {cursor:?}",
-                                file!(),
-                                line!()
-                            );
-                            return Err(err);
+                            let at_end = word == "$end";
+                            let error = ParseError::UnknownKeyword {
+                                keyword: word.to_string(),
+                                expected: "`$scope`, `$comment`, or `$upscope` in an \
+                                           anonymous scope"
+                                    .to_string(),
+                                cursor,
+                            };
+                            if word == "$enddefinitions" {
+                                // the scope was never closed, so we close it
+                                // along with all of its parents
+                                recovery.recover(vcd, Severity::Warning, error)?;
+                                ident(word_reader, "$end")?;
+                                return Ok(true);
+                            }
+                            recovery.recover(vcd, Severity::Error, error)?;
+                            skip_declaration(word_reader, at_end)?;
                         }
                     }
                 }
                 _ => {
-                    let err = format!(
-                        "Error near {}:{}. \
-                                      found keyword `{matched}` but \
-                                      expected `$` on {cursor:?}",
-                        file!(),
-                        line!()
-                    );
-                    return Err(err);
+                    // a stray word, which we skip by itself
+                    let error = ParseError::UnknownKeyword {
+                        keyword: word.to_string(),
+                        expected: "a keyword starting with `$`".to_string(),
+                        cursor,
+                    };
+                    recovery.recover(vcd, Severity::Error, error)?;
                 }
             }
         }
     }
 
-    Ok(())
+    Ok(false)
 }
 
-pub(super) fn parse_scopes<R: std::io::Read>(
-    word_reader: &mut WordReader<R>,
+pub(super) fn parse_scopes<W: WordSource>(
+    word_reader: &mut W,
     vcd: &mut VCD,
     signal_map: &mut HashMap<String, SignalIdx>,
-) -> Result<(), String> {
+    recovery: &mut Recovery,
+) -> Result<(), ParseError> {
     // get the current word
     let (word, _) = curr_word!(word_reader)?;
 
     // we may have orphaned vars that occur before the first scope
     if word == "$var" {
-        parse_orphaned_vars(word_reader, vcd, signal_map)?;
+        parse_orphaned_vars(word_reader, vcd, signal_map, recovery)?;
     }
 
     // get the current word
@@ -465,18 +495,17 @@ pub(super) fn parse_scopes<R: std::io::Read>(
     // If `parse_orphaned_vars` was not called, `parse_scopes` should still
     // have only been called if the caller encountered the word "$scope"
     if word != "$scope" {
-        let msg = format!(
-            "Error near {}:{}.\
-                           Expected $scope or $var, found \
-                           {word} at {cursor:?}",
-            file!(),
-            line!()
-        );
-        return Err(msg);
+        return Err(ParseError::UnknownKeyword {
+            keyword: word.to_string(),
+            expected: "`$scope` or `$var`".to_string(),
+            cursor,
+        });
     }
 
     // now for the interesting part
-    parse_scopes_inner(word_reader, None, vcd, signal_map, &vec![])?;
+    if parse_scopes_inner(word_reader, None, vcd, signal_map, &[], recovery)? {
+        return Ok(());
+    }
 
     // let err = format!("reached end of file without parser leaving {}", function_name!());
     let expected_keywords = ["$scope", "$enddefinitions"];
@@ -490,30 +519,49 @@ pub(super) fn parse_scopes<R: std::io::Read>(
 
         match word {
             "$scope" => {
-                parse_scopes_inner(word_reader, None, vcd, signal_map, &vec![])?;
+                if parse_scopes_inner(word_reader, None, vcd, signal_map, &[], recovery)? {
+                    break;
+                }
             }
             "$enddefinitions" => {
                 ident(word_reader, "$end")?;
                 break;
             }
             "$comment" => {
-                // although we don't store comments, we still need to advance the
-                // word_reader cursor to the end of the comment
-                loop {
-                    if ident(word_reader, "$end").is_ok() {
-                        break;
-                    }
-                }
+                // comments between the signal trees are kept with the
+                // rest of the header
+                let comment = parse_section_text(word_reader)?;
+                vcd.metadata
+                    .extra_sections
+                    .push(HeaderSection::Comment(comment));
             }
             _ => {
-                let err = format!(
-                    "Error near {}:{} \
-                                found keyword `{word}` but expected one of \
-                                {expected_keywords:?} on {cursor:?}",
-                    file!(),
-                    line!()
-                );
-                return Err(err);
+                let is_declaration = word.starts_with('$') && word != "$end";
+                let error = ParseError::UnknownKeyword {
+                    keyword: word.to_string(),
+                    expected: format!("one of {expected_keywords:?}"),
+                    cursor,
+                };
+                recovery.recover(vcd, Severity::Error, error)?;
+                if is_declaration {
+                    skip_declaration(word_reader, false)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Skips the rest of a declaration that couldn't be parsed, up to and
+/// including its `$end`. ``at_end`` tells whether the offending word was
+/// that `$end` already.
+fn skip_declaration<W: WordSource>(word_reader: &mut W, at_end: bool) -> Result<(), ParseError> {
+    if !at_end {
+        loop {
+            let (word, _) = next_word!(word_reader)?;
+            if word == "$end" {
+                break;
             }
         }
     }
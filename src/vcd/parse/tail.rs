@@ -0,0 +1,158 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use super::super::error::ParseError;
+use super::super::reader::{ReaderPosition, SliceWordReader};
+use super::super::types::{SignalIdx, VCD};
+use super::events::{complete_events_end, parse_events, sort_value_changes, EventParserState};
+use super::parse_header;
+use super::progress::ProgressMonitor;
+
+/// A VCD that is still being written to, typically by a simulation that
+/// is still running. The header is parsed once when the file is opened,
+/// after which ``poll_new_events`` appends whatever value changes have
+/// been written since the previous call.
+#[derive(Debug)]
+pub struct TailingVCD {
+    vcd: VCD,
+    file: File,
+    signal_map: HashMap<String, SignalIdx>,
+    events_state: EventParserState,
+    /// Bytes read from the file that have yet to be parsed, starting at
+    /// the beginning of a line. A simulator may be in the middle of
+    /// writing a line or a value change when we poll, so we hold on to
+    /// it until it is finished.
+    pending: Vec<u8>,
+    /// Where in ``pending`` parsing picks up again.
+    offset: usize,
+    /// The line number of the first line in ``pending``.
+    curr_line: usize,
+    /// The byte offset of ``pending`` in the file.
+    consumed: usize,
+}
+
+/// Opens the VCD at ``path`` for tailing. The file must already contain
+/// its complete header, that is, everything up to `$enddefinitions $end`.
+pub fn tail_vcd_from_path(path: impl AsRef<Path>) -> Result<TailingVCD, ParseError> {
+    let path = path.as_ref();
+    let mut file =
+        File::open(path).map_err(|e| ParseError::io(format_args!("failed to open {path:?}"), e))?;
+
+    let mut pending = vec![];
+    file.read_to_end(&mut pending)
+        .map_err(|e| ParseError::io(format_args!("failed to read {path:?}"), e))?;
+
+    let complete_len = complete_lines_len(&pending);
+    let mut word_reader = SliceWordReader::new(&pending[..complete_len]);
+    let (vcd, signal_map) = parse_header(&mut word_reader, &ProgressMonitor::disabled(), false)?;
+
+    let ReaderPosition {
+        offset,
+        line,
+        line_start,
+    } = word_reader.position();
+    pending.drain(..line_start);
+
+    let mut tail = TailingVCD {
+        vcd,
+        file,
+        signal_map,
+        events_state: EventParserState::default(),
+        pending,
+        offset: offset - line_start,
+        curr_line: line,
+        consumed: line_start,
+    };
+    tail.parse_pending()?;
+
+    Ok(tail)
+}
+
+impl TailingVCD {
+    /// Parses any value changes that have been written to the file since
+    /// the last poll. Returns the number of bytes that were consumed, which
+    /// is ``0`` when no complete value change has been written since.
+    ///
+    /// When the new value changes are malformed, the value changes before
+    /// the problem are kept and the error is returned. The next poll picks
+    /// up after the offending value change.
+    pub fn poll_new_events(&mut self) -> Result<usize, ParseError> {
+        self.file
+            .read_to_end(&mut self.pending)
+            .map_err(|e| ParseError::io("failed to read new events", e))?;
+
+        self.parse_pending()
+    }
+
+    /// Parses the complete value changes in ``pending`` and drops the lines
+    /// that were parsed in full.
+    fn parse_pending(&mut self) -> Result<usize, ParseError> {
+        let position = ReaderPosition {
+            offset: self.offset,
+            line: self.curr_line,
+            line_start: 0,
+        };
+        let complete_len = complete_lines_len(&self.pending);
+        let complete = &self.pending[..complete_len];
+        let end = complete_events_end(
+            SliceWordReader::resume(complete, self.consumed, position),
+            &self.events_state,
+        );
+
+        let mut word_reader = SliceWordReader::resume(&complete[..end], self.consumed, position);
+        let result = parse_events(
+            &mut word_reader,
+            &mut self.vcd,
+            &self.signal_map,
+            &mut self.events_state,
+            &ProgressMonitor::disabled(),
+        );
+
+        // the VCD is queried between polls, so blocks that went back in time
+        // are merged into place straight away rather than once at the end
+        if self.events_state.out_of_order {
+            sort_value_changes(&mut self.vcd);
+            // the block being recorded may carry on in the next poll
+            self.events_state.out_of_order =
+                self.events_state.curr_tmstmp.as_ref() != self.vcd.largest_timestamp.as_ref();
+        }
+
+        // whatever was parsed stays parsed, even when parsing failed
+        let ReaderPosition {
+            offset,
+            line,
+            line_start,
+        } = word_reader.position();
+        let parsed = offset - self.offset;
+        self.pending.drain(..line_start);
+        self.offset = offset - line_start;
+        self.curr_line = line;
+        self.consumed += line_start;
+
+        result.map(|()| parsed)
+    }
+
+    /// The VCD as parsed so far.
+    pub fn vcd(&self) -> &VCD {
+        &self.vcd
+    }
+
+    /// Stops tailing and hands back the VCD as parsed so far.
+    pub fn into_vcd(self) -> VCD {
+        self.vcd
+    }
+}
+
+/// The length of the longest prefix of ``bytes`` that ends in a newline.
+fn complete_lines_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .rposition(|chr| *chr == b'\n')
+        .map_or(0, |idx| idx + 1)
+}
@@ -0,0 +1,71 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use super::super::error::{Diagnostic, ParseError, Severity};
+use super::super::reader::WordReader;
+use super::events::{parse_events, EventParserState};
+use super::progress::ProgressMonitor;
+use super::{compression, parse_header};
+
+/// Checks a VCD against the format described in section 18 of IEEE
+/// 1364-2005, and returns every violation found in the order they occur.
+/// The VCD is parsed leniently, with the value changes also checked for
+/// what parsing would otherwise tolerate: repeated timestamps, keywords
+/// that are out of place or never closed, and values outside of four
+/// valued logic. Timestamps that go back in time are always rejected, and
+/// a signal is only reported the first time one of its values doesn't fit.
+///
+/// Only problems reading the input are returned as errors. A dump so
+/// broken that the lenient parser gives up on it is reported as the last
+/// diagnostic.
+pub fn validate_vcd(file: impl Read) -> Result<Vec<Diagnostic>, ParseError> {
+    let mut word_reader = WordReader::new(file);
+    let monitor = ProgressMonitor::disabled();
+    let (mut vcd, signal_map) = match parse_header(&mut word_reader, &monitor, true) {
+        Ok(header) => header,
+        Err(error) if error.cursor().is_some() => {
+            return Ok(vec![Diagnostic {
+                severity: Severity::Error,
+                error,
+            }])
+        }
+        Err(error) => return Err(error),
+    };
+
+    let mut state = EventParserState::validating();
+    match parse_events(
+        &mut word_reader,
+        &mut vcd,
+        &signal_map,
+        &mut state,
+        &monitor,
+    ) {
+        Ok(()) => {}
+        // such as a timestamp too large for the timeline
+        Err(error) if error.cursor().is_some() => vcd.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            error,
+        }),
+        Err(error) => return Err(error),
+    }
+
+    Ok(vcd.diagnostics)
+}
+
+/// Like ``validate_vcd``, but opens the file at ``path``, decompressing it
+/// if need be.
+pub fn validate_vcd_from_path(path: impl AsRef<Path>) -> Result<Vec<Diagnostic>, ParseError> {
+    let path = path.as_ref();
+    let mut file =
+        File::open(path).map_err(|e| ParseError::io(format_args!("failed to open {path:?}"), e))?;
+
+    match compression::detect_compression(&mut file)? {
+        Some(compression) => validate_vcd(compression::decompress(file, compression)?),
+        None => validate_vcd(file),
+    }
+}
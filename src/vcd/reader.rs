@@ -8,18 +8,44 @@ use std::io::BufRead;
 use std::slice;
 use std::str;
 
-#[derive(Debug, Clone)]
-pub(super) struct Line(pub(super) usize);
-#[derive(Debug, Clone)]
-pub(super) struct Word(pub(super) usize);
-#[derive(Debug, Clone)]
-pub(super) struct Cursor(pub(super) Line, pub(super) Word);
+use super::error::{Cursor, ParseError};
+
+/// What a word that isn't valid UTF-8 is read as. VCDs are meant to be
+/// ASCII, but comments and names written by some tools aren't.
+const INVALID_WORD: &str = "\u{FFFD}";
+
+fn word_from_utf8(bytes: &[u8]) -> &str {
+    str::from_utf8(bytes).unwrap_or(INVALID_WORD)
+}
+
+/// Anything the parser can pull whitespace separated words out of.
+/// ``WordReader`` copies lines out of an ``io::Read`` and is used for
+/// pipes and other streams, while ``SliceWordReader`` hands out words
+/// that borrow directly from an in memory or memory mapped buffer.
+pub(super) trait WordSource {
+    fn next_word(&mut self) -> Option<(&str, Cursor)>;
+    fn curr_word(&mut self) -> Option<(&str, Cursor)>;
+    /// The number of input bytes that have been tokenized so far.
+    fn bytes_consumed(&self) -> usize;
+    /// Where the reader currently is. Points just past the last word
+    /// handed out, which at the end of the input is the end of the input.
+    fn cursor(&self) -> Cursor;
+    /// The error that made ``next_word`` stop early, if any. Once a
+    /// reader fails, it behaves as if it reached the end of the input.
+    fn take_error(&mut self) -> Option<ParseError> {
+        None
+    }
+}
 
 pub(super) struct WordReader<R: io::Read> {
     reader: io::BufReader<R>,
     eof: bool,
-    buffers: Vec<String>,
+    bytes_read: usize,
+    error: Option<io::Error>,
+    buffers: Vec<Vec<u8>>,
     curr_line: usize,
+    /// Just past the last byte read so far.
+    end: Cursor,
     str_slices: VecDeque<(*const u8, usize, Cursor)>,
     curr_slice: Option<(*const u8, usize, Cursor)>,
 }
@@ -30,20 +56,32 @@ impl<R: std::io::Read> WordReader<R> {
         WordReader {
             reader,
             eof: false,
+            bytes_read: 0,
+            error: None,
             buffers: vec![],
             curr_line: 0,
+            end: Cursor {
+                line: 1,
+                column: 1,
+                byte_offset: 0,
+            },
             str_slices: VecDeque::new(),
             curr_slice: None,
         }
     }
+}
 
-    pub(super) fn next_word(&mut self) -> Option<(&str, Cursor)> {
+impl<R: std::io::Read> WordSource for WordReader<R> {
+    fn next_word(&mut self) -> Option<(&str, Cursor)> {
         // although reaching the eof is not technically an error, in most cases,
         // we treat it like one in the rest of the codebase.
 
-        // if there are no more words in the buffer, attempt to read more content
-        // from the file
-        if self.str_slices.is_empty() {
+        // if there are no more words in the buffer, read more content from
+        // the file until we either find a word or reach the end of the file,
+        // as a batch of lines may well be blank
+        while self.str_slices.is_empty() {
+            // the current word lives in the buffers we're about to drop
+            self.curr_slice = None;
             self.buffers.clear();
 
             if self.eof {
@@ -53,9 +91,18 @@ impl<R: std::io::Read> WordReader<R> {
             let num_buffers = 10;
 
             for buf_idx in 0..num_buffers {
-                self.buffers.push(String::new());
+                self.buffers.push(Vec::new());
                 self.curr_line += 1;
-                let bytes_read = self.reader.read_line(&mut self.buffers[buf_idx]).unwrap();
+                let line_start = self.bytes_read;
+                let bytes_read = match self.reader.read_until(b'\n', &mut self.buffers[buf_idx]) {
+                    Ok(bytes_read) => bytes_read,
+                    Err(error) => {
+                        self.error = Some(error);
+                        self.eof = true;
+                        break;
+                    }
+                };
+                self.bytes_read += bytes_read;
 
                 // if we've reached the end of the file on the first attempt to read
                 // a line in this for loop, no further attempts are necessary and we
@@ -64,66 +111,252 @@ impl<R: std::io::Read> WordReader<R> {
                     break;
                 }
 
-                let words = self.buffers[buf_idx].split_ascii_whitespace();
+                let buffer = &self.buffers[buf_idx];
+                self.end = if buffer.ends_with(b"\n") {
+                    Cursor {
+                        line: self.curr_line + 1,
+                        column: 1,
+                        byte_offset: self.bytes_read,
+                    }
+                } else {
+                    Cursor {
+                        line: self.curr_line,
+                        column: bytes_read + 1,
+                        byte_offset: self.bytes_read,
+                    }
+                };
 
-                for word in words.enumerate() {
-                    let (word_idx, word) = word;
-                    let position = Cursor(Line(self.curr_line), Word(word_idx + 1));
+                let words = buffer
+                    .split(u8::is_ascii_whitespace)
+                    .filter(|word| !word.is_empty());
+                for word in words {
+                    let column = word.as_ptr() as usize - buffer.as_ptr() as usize;
+                    let position = Cursor {
+                        line: self.curr_line,
+                        column: column + 1,
+                        byte_offset: line_start + column,
+                    };
                     self.str_slices
                         .push_back((word.as_ptr(), word.len(), position))
                 }
             }
         }
 
-        // if after we've attempted to read in more content from the file,
-        // there are still no words...
-        if self.str_slices.is_empty() {
-            return None;
-        }
-
         // if we make it here, we return the next word
         unsafe {
             let (ptr, len, position) = self.str_slices.pop_front().unwrap();
             let slice = slice::from_raw_parts(ptr, len);
-            self.curr_slice = Some((ptr, len, position.clone()));
-            return Some((str::from_utf8(slice).unwrap(), position));
-        };
+            self.curr_slice = Some((ptr, len, position));
+            Some((word_from_utf8(slice), position))
+        }
     }
 
-    pub(super) fn curr_word(&mut self) -> Option<(&str, Cursor)> {
+    fn curr_word(&mut self) -> Option<(&str, Cursor)> {
         match &self.curr_slice {
             Some(slice) => unsafe {
-                let (ptr, len, position) = slice.clone();
+                let (ptr, len, position) = *slice;
                 let slice = slice::from_raw_parts(ptr, len);
-                return Some((str::from_utf8(slice).unwrap(), position));
+                Some((word_from_utf8(slice), position))
             },
             None => None,
         }
     }
+
+    fn bytes_consumed(&self) -> usize {
+        self.bytes_read
+    }
+
+    fn take_error(&mut self) -> Option<ParseError> {
+        let error = self.error.take()?;
+        Some(ParseError::io(
+            format!("failed to read line {}", self.curr_line),
+            error,
+        ))
+    }
+
+    fn cursor(&self) -> Cursor {
+        match (&self.curr_slice, self.str_slices.front()) {
+            // somewhere in the middle of the lines read so far
+            (Some((_, len, position)), Some(_)) => Cursor {
+                column: position.column + len,
+                byte_offset: position.byte_offset + len,
+                ..*position
+            },
+            _ => self.end,
+        }
+    }
+}
+
+/// Where a ``SliceWordReader`` is in its buffer: the byte offset just past
+/// the last word handed out, the line that offset is on, and the offset at
+/// which that line starts.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ReaderPosition {
+    pub(super) offset: usize,
+    pub(super) line: usize,
+    pub(super) line_start: usize,
+}
+
+/// Tokenizes a borrowed buffer in place. Unlike ``WordReader``, no line
+/// is ever copied, so the words handed out live as long as the buffer.
+pub(super) struct SliceWordReader<'a> {
+    bytes: &'a [u8],
+    /// The byte offset of ``bytes`` in the input, for when the buffer
+    /// doesn't start at the beginning of the file.
+    base: usize,
+    pos: usize,
+    curr_line: usize,
+    line_start: usize,
+    curr_word: Option<(&'a str, Cursor)>,
+}
+
+impl<'a> SliceWordReader<'a> {
+    pub(super) fn new(bytes: &'a [u8]) -> SliceWordReader<'a> {
+        SliceWordReader::resume(
+            bytes,
+            0,
+            ReaderPosition {
+                offset: 0,
+                line: 1,
+                line_start: 0,
+            },
+        )
+    }
+
+    /// Picks up tokenizing ``bytes`` at ``position``, with ``bytes`` itself
+    /// starting ``base`` bytes into the input. This lets a reader report the
+    /// same cursors it would have had if the whole input had been read in
+    /// one go.
+    pub(super) fn resume(
+        bytes: &'a [u8],
+        base: usize,
+        position: ReaderPosition,
+    ) -> SliceWordReader<'a> {
+        SliceWordReader {
+            bytes,
+            base,
+            pos: position.offset,
+            curr_line: position.line,
+            line_start: position.line_start,
+            curr_word: None,
+        }
+    }
+
+    pub(super) fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub(super) fn position(&self) -> ReaderPosition {
+        ReaderPosition {
+            offset: self.pos,
+            line: self.curr_line,
+            line_start: self.line_start,
+        }
+    }
+
+    /// A reader over the same buffer that starts at ``offset`` and stops at
+    /// ``end``. ``offset`` must not lie before this reader's position, nor in
+    /// the middle of a word.
+    pub(super) fn slice(&self, offset: usize, end: usize) -> SliceWordReader<'a> {
+        let skipped = &self.bytes[self.pos..offset];
+        let newlines = skipped.iter().filter(|chr| **chr == b'\n').count();
+        let line_start = match skipped.iter().rposition(|chr| *chr == b'\n') {
+            Some(newline_idx) => self.pos + newline_idx + 1,
+            None => self.line_start,
+        };
+
+        SliceWordReader::resume(
+            &self.bytes[..end],
+            self.base,
+            ReaderPosition {
+                offset,
+                line: self.curr_line + newlines,
+                line_start,
+            },
+        )
+    }
+
+    fn cursor_at(&self, offset: usize) -> Cursor {
+        Cursor {
+            line: self.curr_line,
+            column: offset - self.line_start + 1,
+            byte_offset: self.base + offset,
+        }
+    }
+
+    pub(super) fn next_word(&mut self) -> Option<(&'a str, Cursor)> {
+        let bytes = self.bytes;
+
+        // skip over whitespace, keeping track of lines as we go so that
+        // cursors match the ones produced by ``WordReader``
+        while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+            if bytes[self.pos] == b'\n' {
+                self.curr_line += 1;
+                self.line_start = self.pos + 1;
+            }
+            self.pos += 1;
+        }
+
+        if self.pos == bytes.len() {
+            return None;
+        }
+
+        let start = self.pos;
+        while self.pos < bytes.len() && !bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+
+        let word = word_from_utf8(&bytes[start..self.pos]);
+        let position = self.cursor_at(start);
+        self.curr_word = Some((word, position));
+        Some((word, position))
+    }
+
+    pub(super) fn curr_word(&self) -> Option<(&'a str, Cursor)> {
+        self.curr_word
+    }
+}
+
+impl<'a> WordSource for SliceWordReader<'a> {
+    fn next_word(&mut self) -> Option<(&str, Cursor)> {
+        SliceWordReader::next_word(self)
+    }
+
+    fn curr_word(&mut self) -> Option<(&str, Cursor)> {
+        SliceWordReader::curr_word(self)
+    }
+
+    fn bytes_consumed(&self) -> usize {
+        self.pos
+    }
+
+    fn cursor(&self) -> Cursor {
+        self.cursor_at(self.pos)
+    }
 }
 
 macro_rules! next_word {
     ($word_reader:ident) => {
-        $word_reader.next_word().ok_or(()).map_err(|_| {
-            format!(
-                "Error near {}:{}. Did not expect to reach end of file here.",
-                file!(),
-                line!()
-            )
-        })
+        match $word_reader.next_word() {
+            Some(word) => Ok(word),
+            None => Err(match $word_reader.take_error() {
+                Some(error) => error,
+                None => $crate::vcd::error::ParseError::UnexpectedEof {
+                    cursor: $word_reader.cursor(),
+                },
+            }),
+        }
     };
 }
 
 macro_rules! curr_word {
     ($word_reader:ident) => {
-        $word_reader.curr_word().ok_or(()).map_err(|_| {
-            format!(
-                "Error near {}:{}. A call to curr_word! shouldn't \
-                 fail unless next_word has not yet been invoked.",
-                file!(),
-                line!()
-            )
-        })
+        match $word_reader.curr_word() {
+            Some(word) => Ok(word),
+            None => Err($crate::vcd::error::ParseError::internal(
+                "curr_word! was invoked before next_word!",
+            )),
+        }
     };
 }
 
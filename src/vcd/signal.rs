@@ -2,15 +2,18 @@
 // This program is distributed under both the GPLV3 license
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
+use super::error::{Cursor, ParseError};
 use super::types;
 use super::types::SignalIdx;
-use num::BigUint;
+use num::{BigUint, ToPrimitive};
 
 // Index to the least significant byte of a timestamp
 // value on the timeline
 #[derive(Debug, Copy, Clone)]
 pub struct LsbIdxOfTmstmpValOnTmln(pub(super) u32);
 
+/// The type of a `$var`. Besides the types of IEEE 1364, these include the
+/// SystemVerilog types and the extensions GTKWave reads.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum SignalType {
     Event,
@@ -32,12 +35,332 @@ pub enum SignalType {
     WAnd,
     Wire,
     WOr,
+    /// A port of an extended VCD, declared with `$var port`.
+    Port,
+    Logic,
+    Bit,
+    Int,
+    ShortInt,
+    LongInt,
+    Byte,
+    Enum,
+    ShortReal,
+    /// A sparse array, declared with `$var sparray`.
+    SparseArray,
+    /// A type we don't know of, as it was written.
+    Other(String),
+}
+
+impl SignalType {
+    /// The type written as ``keyword`` in a `$var`.
+    pub fn from_keyword(keyword: &str) -> SignalType {
+        match keyword {
+            "event" => SignalType::Event,
+            "integer" => SignalType::Integer,
+            "parameter" => SignalType::Parameter,
+            "real" => SignalType::Real,
+            "realtime" => SignalType::RealTime,
+            "reg" => SignalType::Reg,
+            "string" => SignalType::Str,
+            "supply0" => SignalType::Supply0,
+            "supply1" => SignalType::Supply1,
+            "time" => SignalType::Time,
+            "tri" => SignalType::Tri,
+            "triand" => SignalType::TriAnd,
+            "trior" => SignalType::TriOr,
+            "trireg" => SignalType::TriReg,
+            "tri0" => SignalType::Tri0,
+            "tri1" => SignalType::Tri1,
+            "wand" => SignalType::WAnd,
+            "wire" => SignalType::Wire,
+            "wor" => SignalType::WOr,
+            "port" => SignalType::Port,
+            "logic" => SignalType::Logic,
+            "bit" => SignalType::Bit,
+            "int" => SignalType::Int,
+            "shortint" => SignalType::ShortInt,
+            "longint" => SignalType::LongInt,
+            "byte" => SignalType::Byte,
+            "enum" => SignalType::Enum,
+            "shortreal" => SignalType::ShortReal,
+            "sparray" => SignalType::SparseArray,
+            other => SignalType::Other(other.to_string()),
+        }
+    }
+
+    /// The keyword the type is written as in a `$var`.
+    pub fn keyword(&self) -> &str {
+        match self {
+            SignalType::Event => "event",
+            SignalType::Integer => "integer",
+            SignalType::Parameter => "parameter",
+            SignalType::Real => "real",
+            SignalType::RealTime => "realtime",
+            SignalType::Reg => "reg",
+            SignalType::Str => "string",
+            SignalType::Supply0 => "supply0",
+            SignalType::Supply1 => "supply1",
+            SignalType::Time => "time",
+            SignalType::Tri => "tri",
+            SignalType::TriAnd => "triand",
+            SignalType::TriOr => "trior",
+            SignalType::TriReg => "trireg",
+            SignalType::Tri0 => "tri0",
+            SignalType::Tri1 => "tri1",
+            SignalType::WAnd => "wand",
+            SignalType::Wire => "wire",
+            SignalType::WOr => "wor",
+            SignalType::Port => "port",
+            SignalType::Logic => "logic",
+            SignalType::Bit => "bit",
+            SignalType::Int => "int",
+            SignalType::ShortInt => "shortint",
+            SignalType::LongInt => "longint",
+            SignalType::Byte => "byte",
+            SignalType::Enum => "enum",
+            SignalType::ShortReal => "shortreal",
+            SignalType::SparseArray => "sparray",
+            SignalType::Other(keyword) => keyword,
+        }
+    }
+
+    /// Whether values of this type may be `x` or `z` besides 0 and 1. Reals
+    /// and strings count as two-state, as do the types we don't know of.
+    pub fn is_four_state(&self) -> bool {
+        match self {
+            SignalType::Integer
+            | SignalType::Parameter
+            | SignalType::Reg
+            | SignalType::Supply0
+            | SignalType::Supply1
+            | SignalType::Time
+            | SignalType::Tri
+            | SignalType::TriAnd
+            | SignalType::TriOr
+            | SignalType::TriReg
+            | SignalType::Tri0
+            | SignalType::Tri1
+            | SignalType::WAnd
+            | SignalType::Wire
+            | SignalType::WOr
+            | SignalType::Port
+            | SignalType::Logic
+            | SignalType::SparseArray => true,
+            SignalType::Event
+            | SignalType::Real
+            | SignalType::RealTime
+            | SignalType::Str
+            | SignalType::Bit
+            | SignalType::Int
+            | SignalType::ShortInt
+            | SignalType::LongInt
+            | SignalType::Byte
+            | SignalType::Enum
+            | SignalType::ShortReal
+            | SignalType::Other(_) => false,
+        }
+    }
+
+    /// Whether values of this type are floating point numbers, which are
+    /// kept as ``f64``s whatever their declared width.
+    pub fn is_real(&self) -> bool {
+        matches!(
+            self,
+            SignalType::Real | SignalType::RealTime | SignalType::ShortReal
+        )
+    }
+
+    /// The width a variable of this type has unless declared otherwise, or
+    /// ``None`` for types whose width always has to be declared, and for
+    /// strings.
+    pub fn default_width(&self) -> Option<u32> {
+        match self {
+            SignalType::Event
+            | SignalType::Reg
+            | SignalType::Supply0
+            | SignalType::Supply1
+            | SignalType::Tri
+            | SignalType::TriAnd
+            | SignalType::TriOr
+            | SignalType::TriReg
+            | SignalType::Tri0
+            | SignalType::Tri1
+            | SignalType::WAnd
+            | SignalType::Wire
+            | SignalType::WOr
+            | SignalType::Port
+            | SignalType::Logic
+            | SignalType::Bit => Some(1),
+            SignalType::Byte => Some(8),
+            SignalType::ShortInt => Some(16),
+            // enums are ints unless given another base type
+            SignalType::Integer | SignalType::Int | SignalType::Enum | SignalType::ShortReal => {
+                Some(32)
+            }
+            SignalType::Time | SignalType::LongInt | SignalType::Real | SignalType::RealTime => {
+                Some(64)
+            }
+            SignalType::Parameter
+            | SignalType::Str
+            | SignalType::SparseArray
+            | SignalType::Other(_) => None,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SignalValue {
     BigUint(BigUint),
     String(String),
+    /// The value of a ``SignalType::Real``, ``SignalType::RealTime`` or
+    /// ``SignalType::ShortReal``.
+    Real(f64),
+    /// The value of a ``SignalType::Port``.
+    Port(PortValue),
+}
+
+/// The value of a port of an extended VCD, such as `pDZ 6 0 <12`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortValue {
+    /// One state character per bit, most significant bit first. See
+    /// ``PortDirection::of_state`` for what they mean.
+    pub states: String,
+    /// The strength of the port's 0 drivers, from 0 (highz) to 7 (supply).
+    pub strength0: u8,
+    /// The strength of the port's 1 drivers, from 0 (highz) to 7 (supply).
+    pub strength1: u8,
+}
+
+impl PortValue {
+    /// Reads a port value change without its `p` and identifier, given as
+    /// its states and two strengths.
+    pub(super) fn parse(states: &str, strength0: &str, strength1: &str) -> Option<PortValue> {
+        let strength = |strength: &str| match strength.as_bytes() {
+            [digit @ b'0'..=b'7'] => Some(digit - b'0'),
+            _ => None,
+        };
+        let valid_states = !states.is_empty()
+            && states
+                .chars()
+                .all(|state| PortDirection::of_state(state).is_some());
+        valid_states.then_some(PortValue {
+            states: states.to_string(),
+            strength0: strength(strength0)?,
+            strength1: strength(strength1)?,
+        })
+    }
+
+    /// Reads a port value back from the text its ``Display`` produces,
+    /// which is how the value changes of ports are stored.
+    pub(super) fn decode(text: &str) -> Option<PortValue> {
+        let mut parts = text.split(' ');
+        let value = PortValue::parse(parts.next()?, parts.next()?, parts.next()?)?;
+        parts.next().is_none().then_some(value)
+    }
+
+    /// The direction of every bit, most significant bit first.
+    pub fn directions(&self) -> impl Iterator<Item = PortDirection> + '_ {
+        self.states
+            .chars()
+            .map(|state| PortDirection::of_state(state).unwrap_or(PortDirection::Unknown))
+    }
+}
+
+impl std::fmt::Display for PortValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.states, self.strength0, self.strength1)
+    }
+}
+
+/// Which side drives a bit of an extended VCD port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    /// The test fixture drives the port.
+    Input,
+    /// The device under test drives the port.
+    Output,
+    /// Both sides drive the port, or neither does.
+    Unknown,
+}
+
+impl PortDirection {
+    /// The direction a state character of IEEE 1364-2005 section 18.4.3.1
+    /// implies, or ``None`` if ``state`` isn't one.
+    ///
+    /// | Direction | Low    | High   | Unknown | High impedance |
+    /// |-----------|--------|--------|---------|----------------|
+    /// | Input     | `D` `d`| `U` `u`| `N`     | `Z`            |
+    /// | Output    | `L` `l`| `H` `h`| `X` `x` | `T`            |
+    /// | Unknown   | `0`    | `1`    | `?` `A` `a` `B` `b` `C` `c` | `F` `f` |
+    ///
+    /// Lowercase `d`, `u`, `l` and `h` mean two or more drivers are active.
+    pub fn of_state(state: char) -> Option<PortDirection> {
+        match state {
+            'D' | 'd' | 'U' | 'u' | 'N' | 'Z' => Some(PortDirection::Input),
+            'L' | 'l' | 'H' | 'h' | 'X' | 'x' | 'T' => Some(PortDirection::Output),
+            '0' | '1' | '?' | 'A' | 'a' | 'B' | 'b' | 'C' | 'c' | 'F' | 'f' => {
+                Some(PortDirection::Unknown)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The bits a vector is declared with, such as `[7:0]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitRange {
+    pub msb: i64,
+    pub lsb: i64,
+}
+
+impl BitRange {
+    /// The number of bits in the range, whichever way around it is.
+    pub fn width(&self) -> u32 {
+        // a range spanning every i64 has one more bit than a u64 can count
+        self.msb
+            .abs_diff(self.lsb)
+            .checked_add(1)
+            .and_then(|width| u32::try_from(width).ok())
+            .unwrap_or(u32::MAX)
+    }
+}
+
+/// The index a `$var` is declared with, which selects either a single bit,
+/// as in `[3]`, or a range of bits, as in `[7:0]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitIndex {
+    Bit(i64),
+    Range(BitRange),
+}
+
+impl BitIndex {
+    /// Reads an index such as `[3]` or `[7:0]`, brackets included.
+    pub fn parse(text: &str) -> Option<BitIndex> {
+        let inner = text.strip_prefix('[')?.strip_suffix(']')?;
+        match inner.split_once(':') {
+            Some((msb, lsb)) => Some(BitIndex::Range(BitRange {
+                msb: msb.trim().parse().ok()?,
+                lsb: lsb.trim().parse().ok()?,
+            })),
+            None => Some(BitIndex::Bit(inner.trim().parse().ok()?)),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            BitIndex::Bit(_) => 1,
+            BitIndex::Range(range) => range.width(),
+        }
+    }
+}
+
+impl std::fmt::Display for BitIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitIndex::Bit(bit) => write!(f, "[{bit}]"),
+            BitIndex::Range(BitRange { msb, lsb }) => write!(f, "[{msb}:{lsb}]"),
+        }
+    }
 }
 
 pub struct QueryResult<T> {
@@ -67,6 +390,23 @@ impl<'a> Signal<'a> {
         match self.0 {
             SignalEnum::Data { path, .. } => path,
             SignalEnum::Alias { path, .. } => path,
+            SignalEnum::Bus { path, .. } => path,
+        }
+    }
+
+    /// The parsed index of the signal, such as `[7:0]` or `[3]`.
+    pub fn bit_index(&self) -> Option<BitIndex> {
+        let Signal(signal_enum) = &self;
+        signal_enum.bit_index()
+    }
+
+    /// The signals of the bits of a bus assembled with
+    /// ``ParseOptions::assemble_buses``, most significant bit first.
+    /// Returns ``None`` for every other signal.
+    pub fn bus_bits(&self) -> Option<&[SignalIdx]> {
+        match self.0 {
+            SignalEnum::Bus { bits, .. } => Some(bits),
+            _ => None,
         }
     }
 
@@ -79,6 +419,7 @@ impl<'a> Signal<'a> {
         match self.0 {
             SignalEnum::Data { self_idx, .. } => *self_idx,
             SignalEnum::Alias { signal_alias, .. } => *signal_alias,
+            SignalEnum::Bus { self_idx, .. } => *self_idx,
         }
     }
 
@@ -87,6 +428,33 @@ impl<'a> Signal<'a> {
         signal_enum.bits_required()
     }
 
+    /// The signals holding the value changes of this one: the bits of a
+    /// bus, or else the signal this one is an alias of.
+    pub(super) fn data_idxs(&self, vcd: &types::VCD) -> Vec<SignalIdx> {
+        match self.0 {
+            SignalEnum::Bus { bits, .. } => bits
+                .iter()
+                .map(|bit| vcd.signal_from_signal_idx(*bit).real_idx())
+                .collect(),
+            _ => vec![self.real_idx()],
+        }
+    }
+
+    /// Whether value changes were recorded for this signal. This is only
+    /// ever false for signals left out by a ``SignalFilter``. Signals of a
+    /// lazily parsed VCD count as loaded, as they are decoded on demand.
+    pub fn is_loaded(&self, vcd: &types::VCD) -> bool {
+        if let SignalEnum::Bus { bits, .. } = self.0 {
+            return bits
+                .iter()
+                .all(|bit| vcd.signal_from_signal_idx(*bit).is_loaded(vcd));
+        }
+        let real_idx = self.real_idx();
+        let SignalIdx(idx) = real_idx;
+        vcd.lazy.as_ref().is_some_and(|lazy| lazy.selects(real_idx))
+            || matches!(vcd.all_signals[idx], SignalEnum::Data { loaded: true, .. })
+    }
+
     // NOTE: (zoq) I am removing thse because they aren't used in Surfer so I can't test them
     // properly
     /*
@@ -118,7 +486,24 @@ impl<'a> Signal<'a> {
         desired_time: &BigUint,
         vcd: &types::VCD,
     ) -> Result<QueryResult<SignalValue>, SignalErrors> {
-        let Signal(signal_enum) = &self;
+        if let SignalEnum::Bus { bits, .. } = self.0 {
+            return Self::query_bus_val_on_tmln(bits, desired_time, vcd);
+        }
+
+        // look through aliases, and decode lazily parsed signals on demand
+        let SignalIdx(idx) = self.real_idx();
+        let lazily_loaded;
+        let signal_enum = match (&vcd.all_signals[idx], &vcd.lazy) {
+            (SignalEnum::Data { loaded: false, .. }, Some(lazy))
+                if lazy.selects(SignalIdx(idx)) =>
+            {
+                lazily_loaded = lazy
+                    .signal(vcd, SignalIdx(idx))
+                    .map_err(SignalErrors::Parse)?;
+                &*lazily_loaded
+            }
+            (signal_enum, _) => signal_enum,
+        };
         let num_query_out = signal_enum.query_num_val_on_tmln(
             desired_time,
             &vcd.tmstmps_encoded_as_u8s,
@@ -130,6 +515,24 @@ impl<'a> Signal<'a> {
             &vcd.all_signals,
         );
 
+        // the values of reals are kept as the bits of an f64
+        let is_real = signal_enum.signal_type().is_some_and(SignalType::is_real);
+        let numeric_value = |num_value: BigUint| {
+            if is_real {
+                SignalValue::Real(f64::from_bits(num_value.to_u64().unwrap_or_default()))
+            } else {
+                SignalValue::BigUint(num_value)
+            }
+        };
+
+        // the value changes of ports are kept as strings
+        let is_port = signal_enum.signal_type() == Some(&SignalType::Port);
+        let string_value =
+            |str_value: String| match is_port.then(|| PortValue::decode(&str_value)).flatten() {
+                Some(port_value) => SignalValue::Port(port_value),
+                None => SignalValue::String(str_value),
+            };
+
         // Both num and str will return the newest value that is closest to
         // the desired time. If both have valid values, select the most recent
         // one
@@ -146,22 +549,22 @@ impl<'a> Signal<'a> {
                     (Some((num_time, num_value)), Some((str_time, str_value))) => {
                         if num_time > str_time {
                             Ok(QueryResult {
-                                current: Some((num_time, SignalValue::BigUint(num_value))),
+                                current: Some((num_time, numeric_value(num_value))),
                                 next,
                             })
                         } else {
                             Ok(QueryResult {
-                                current: Some((str_time, SignalValue::String(str_value))),
+                                current: Some((str_time, string_value(str_value))),
                                 next,
                             })
                         }
                     }
                     (Some((num_time, num_val)), None) => Ok(QueryResult {
-                        current: Some((num_time, SignalValue::BigUint(num_val))),
+                        current: Some((num_time, numeric_value(num_val))),
                         next,
                     }),
                     (None, Some((str_time, str_value))) => Ok(QueryResult {
-                        current: Some((str_time, SignalValue::String(str_value))),
+                        current: Some((str_time, string_value(str_value))),
                         next,
                     }),
                     (None, None) => Ok(QueryResult {
@@ -174,9 +577,62 @@ impl<'a> Signal<'a> {
             (Err(e), _e) => Err(e),
         }
     }
+
+    /// Assembles the value of a bus from the values of its bits, which
+    /// changes whenever any one of its bits does. A bit that has no value
+    /// yet counts as `x`.
+    fn query_bus_val_on_tmln(
+        bits: &[SignalIdx],
+        desired_time: &BigUint,
+        vcd: &types::VCD,
+    ) -> Result<QueryResult<SignalValue>, SignalErrors> {
+        let mut current_time: Option<TimeStamp> = None;
+        let mut next: Option<TimeStamp> = None;
+        let mut value = String::with_capacity(bits.len());
+
+        for bit in bits {
+            let bit_result = vcd
+                .signal_from_signal_idx(*bit)
+                .query_val_on_tmln(desired_time, vcd)?;
+            next = match (next, bit_result.next) {
+                (Some(next), Some(bit_next)) => Some(next.min(bit_next)),
+                (next, bit_next) => next.or(bit_next),
+            };
+
+            let Some((time, bit_value)) = bit_result.current else {
+                value.push('x');
+                continue;
+            };
+            current_time = Some(match current_time {
+                Some(current_time) => current_time.max(time),
+                None => time,
+            });
+            value.push(match bit_value {
+                SignalValue::BigUint(num) if num == BigUint::from(0u8) => '0',
+                SignalValue::BigUint(_) => '1',
+                SignalValue::String(text) => text
+                    .chars()
+                    .last()
+                    .map_or('x', |chr| chr.to_ascii_lowercase()),
+                SignalValue::Real(_) | SignalValue::Port(_) => 'x',
+            });
+        }
+
+        let value = match BigUint::parse_bytes(value.as_bytes(), 2) {
+            Some(num) if value.bytes().all(|chr| chr == b'0' || chr == b'1') => {
+                SignalValue::BigUint(num)
+            }
+            _ => SignalValue::String(value),
+        };
+        Ok(QueryResult {
+            current: current_time.map(|time| (time, value)),
+            next,
+        })
+    }
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub(super) enum SignalEnum {
     Data {
         name: String,
@@ -190,6 +646,9 @@ pub(super) enum SignalEnum {
         /// I consider this to be bad behavior. We capture such
         /// errors in the following type:
         signal_error: Option<String>,
+        /// Whether value changes are recorded for this signal. Signals
+        /// left out by a ``SignalFilter`` are not.
+        loaded: bool,
         num_bits: Option<u32>,
         num_bytes: Option<u16>,
         /// TODO : may be able to remove self_idx
@@ -223,8 +682,20 @@ pub(super) enum SignalEnum {
     Alias {
         name: String,
         path: Vec<String>,
+        index: Option<String>,
         signal_alias: SignalIdx,
     },
+    /// A virtual vector assembled from single bit signals that share a
+    /// name, such as `data [0]`, `data [1]` and so on.
+    Bus {
+        name: String,
+        path: Vec<String>,
+        signal_type: SignalType,
+        range: BitRange,
+        /// The signals of the bits, most significant bit first.
+        bits: Vec<SignalIdx>,
+        self_idx: SignalIdx,
+    },
 }
 
 #[derive(Debug)]
@@ -239,6 +710,10 @@ pub enum SignalErrors {
     },
     PointsToAlias,
     NoNumBytes,
+    /// The signal was left out by the ``SignalFilter`` it was parsed with.
+    NotLoaded,
+    /// Decoding the value changes of a lazily loaded signal failed.
+    Parse(ParseError),
     Other(String),
 }
 
@@ -252,6 +727,7 @@ impl SignalEnum {
         match self {
             SignalEnum::Data { name, .. } => name,
             SignalEnum::Alias { name, .. } => name,
+            SignalEnum::Bus { name, .. } => name,
         }
         .clone()
     }
@@ -259,47 +735,187 @@ impl SignalEnum {
     pub fn signal_type(&self) -> Option<&SignalType> {
         match self {
             SignalEnum::Data { signal_type, .. } => Some(signal_type),
+            SignalEnum::Bus { signal_type, .. } => Some(signal_type),
             // TODO: Follow aliases?
             SignalEnum::Alias { .. } => None,
         }
-        .clone()
     }
 
     pub fn name_with_index(&self) -> String {
-        match self {
-            SignalEnum::Data {
-                name, index: None, ..
-            } => format!("{name}"),
-            SignalEnum::Data {
-                name,
-                index: Some(size),
-                ..
-            } => format!("{name} {size}"),
-            SignalEnum::Alias { name, .. } => name.clone(),
+        match self.index() {
+            Some(index) => format!("{} {index}", self.name()),
+            None => self.name(),
         }
     }
 
     pub fn index(&self) -> Option<String> {
         match self {
             SignalEnum::Data { index, .. } => index.clone(),
-            SignalEnum::Alias { .. } => None,
+            SignalEnum::Alias { index, .. } => index.clone(),
+            SignalEnum::Bus { range, .. } => Some(BitIndex::Range(*range).to_string()),
+        }
+    }
+
+    /// The parsed index of the signal. Some simulators attach the index to
+    /// the name, as in `data[3]`, which is also understood.
+    pub fn bit_index(&self) -> Option<BitIndex> {
+        match self.index() {
+            Some(index) => BitIndex::parse(&index),
+            None => split_index(&self.name()).map(|(_, bit_index)| bit_index),
+        }
+    }
+
+    /// Gives the signal a new name and path, where ``new_path`` ends with
+    /// the new name.
+    pub(super) fn rename(&mut self, new_name: String, new_path: Vec<String>) {
+        match self {
+            SignalEnum::Data { name, path, .. }
+            | SignalEnum::Alias { name, path, .. }
+            | SignalEnum::Bus { name, path, .. } => {
+                *name = new_name;
+                *path = new_path;
+            }
         }
     }
 }
 
+/// Splits an index attached to the end of ``name``, as in `data[3]`, from
+/// the name. Escaped identifiers may contain brackets, so they are never
+/// split.
+pub(super) fn split_index(name: &str) -> Option<(&str, BitIndex)> {
+    if name.starts_with('\\') {
+        return None;
+    }
+    let start = name.rfind('[')?;
+    let bit_index = BitIndex::parse(&name[start..])?;
+    Some((&name[..start], bit_index))
+}
+
 // helper functions ultimately used by Signal's query functions later on
 impl SignalEnum {
+    /// Makes a copy of this signal's declaration with none of its
+    /// recorded value changes. Used to give each worker parsing a chunk
+    /// of the value change section its own set of signal buffers.
+    pub(super) fn without_values(&self) -> SignalEnum {
+        match self {
+            SignalEnum::Data {
+                name,
+                path,
+                signal_type,
+                index,
+                signal_error,
+                loaded,
+                num_bits,
+                num_bytes,
+                self_idx,
+                ..
+            } => SignalEnum::Data {
+                name: name.clone(),
+                path: path.clone(),
+                signal_type: signal_type.clone(),
+                index: index.clone(),
+                signal_error: signal_error.clone(),
+                loaded: *loaded,
+                num_bits: *num_bits,
+                num_bytes: *num_bytes,
+                self_idx: *self_idx,
+                nums_encoded_as_fixed_width_le_u8: vec![],
+                string_vals: vec![],
+                lsb_indxs_of_num_tmstmp_vals_on_tmln: vec![],
+                byte_len_of_num_tmstmp_vals_on_tmln: vec![],
+                byte_len_of_string_tmstmp_vals_on_tmln: vec![],
+                lsb_indxs_of_string_tmstmp_vals_on_tmln: vec![],
+            },
+            SignalEnum::Alias {
+                name,
+                path,
+                index,
+                signal_alias,
+            } => SignalEnum::Alias {
+                name: name.clone(),
+                path: path.clone(),
+                index: index.clone(),
+                signal_alias: *signal_alias,
+            },
+            SignalEnum::Bus {
+                name,
+                path,
+                signal_type,
+                range,
+                bits,
+                self_idx,
+            } => SignalEnum::Bus {
+                name: name.clone(),
+                path: path.clone(),
+                signal_type: signal_type.clone(),
+                range: *range,
+                bits: bits.clone(),
+                self_idx: *self_idx,
+            },
+        }
+    }
+
+    /// Stably sorts the value changes of this signal by their timestamps
+    /// on ``tmstmps_encoded_as_u8s``, keeping only the last of several
+    /// changes at the same time.
+    pub(super) fn sort_value_changes(&mut self, tmstmps_encoded_as_u8s: &[u8]) {
+        let SignalEnum::Data {
+            num_bytes,
+            nums_encoded_as_fixed_width_le_u8,
+            string_vals,
+            lsb_indxs_of_num_tmstmp_vals_on_tmln,
+            byte_len_of_num_tmstmp_vals_on_tmln,
+            lsb_indxs_of_string_tmstmp_vals_on_tmln,
+            byte_len_of_string_tmstmp_vals_on_tmln,
+            ..
+        } = self
+        else {
+            return;
+        };
+
+        let order = chronological_order(
+            lsb_indxs_of_num_tmstmp_vals_on_tmln,
+            byte_len_of_num_tmstmp_vals_on_tmln,
+            tmstmps_encoded_as_u8s,
+        );
+        let num_bytes = usize::from(num_bytes.unwrap_or_default());
+        if num_bytes > 0 {
+            let nums = std::mem::take(nums_encoded_as_fixed_width_le_u8);
+            *nums_encoded_as_fixed_width_le_u8 = order
+                .iter()
+                .flat_map(|idx| &nums[idx * num_bytes..(idx + 1) * num_bytes])
+                .copied()
+                .collect();
+        }
+        *lsb_indxs_of_num_tmstmp_vals_on_tmln =
+            reorder(lsb_indxs_of_num_tmstmp_vals_on_tmln, &order);
+        *byte_len_of_num_tmstmp_vals_on_tmln = reorder(byte_len_of_num_tmstmp_vals_on_tmln, &order);
+
+        let order = chronological_order(
+            lsb_indxs_of_string_tmstmp_vals_on_tmln,
+            byte_len_of_string_tmstmp_vals_on_tmln,
+            tmstmps_encoded_as_u8s,
+        );
+        *string_vals = reorder(string_vals, &order);
+        *lsb_indxs_of_string_tmstmp_vals_on_tmln =
+            reorder(lsb_indxs_of_string_tmstmp_vals_on_tmln, &order);
+        *byte_len_of_string_tmstmp_vals_on_tmln =
+            reorder(byte_len_of_string_tmstmp_vals_on_tmln, &order);
+    }
+
     /// Computes the bytes required to store a signal's numerical value
     /// using the num_bits which another function would provide from
     /// the num_bits field of the Signal::Data variant.
-    pub(super) fn bytes_required(num_bits: u32, name: &String) -> Result<u16, String> {
-        let bytes_required = (num_bits / 8) + if (num_bits % 8) > 0 { 1 } else { 0 };
+    pub(super) fn bytes_required(
+        num_bits: u32,
+        name: &String,
+        cursor: Cursor,
+    ) -> Result<u16, ParseError> {
+        let bytes_required = num_bits.div_ceil(8);
         let bytes_required = u16::try_from(bytes_required).map_err(|_| {
-            format!(
-                "Error near {}:{}. Signal {name} of length num_bits requires \
-                        {bytes_required} > 65536 bytes.",
-                file!(),
-                line!()
+            ParseError::limit(
+                format!("signal {name} requires {bytes_required} > 65535 bytes"),
+                Some(cursor),
             )
         })?;
         Ok(bytes_required)
@@ -314,7 +930,7 @@ impl SignalEnum {
     fn time_and_str_val_at_event_idx(
         &self,
         event_idx: usize,
-        tmstmps_encoded_as_u8s: &Vec<u8>,
+        tmstmps_encoded_as_u8s: &[u8],
     ) -> Result<(TimeStamp, &str), SignalErrors> {
         let (
             string_vals,
@@ -331,7 +947,7 @@ impl SignalEnum {
                 lsb_indxs_of_string_tmstmp_vals_on_tmln,
                 byte_len_of_string_tmstmp_vals_on_tmln,
             )),
-            SignalEnum::Alias { .. } => Err(SignalErrors::PointsToAlias),
+            SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(SignalErrors::PointsToAlias),
         }?;
 
         // get index
@@ -366,7 +982,7 @@ impl SignalEnum {
     fn time_and_num_val_at_event_idx(
         &self,
         event_idx: usize,
-        tmstmps_encoded_as_u8s: &Vec<u8>,
+        tmstmps_encoded_as_u8s: &[u8],
     ) -> Result<(TimeStamp, SignalValNum), SignalErrors> {
         let (
             num_bytes,
@@ -386,7 +1002,7 @@ impl SignalEnum {
                 lsb_indxs_of_num_tmstmp_vals_on_tmln,
                 byte_len_of_num_tmstmp_vals_on_tmln,
             )),
-            SignalEnum::Alias { .. } => Err(SignalErrors::PointsToAlias),
+            SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(SignalErrors::PointsToAlias),
         }?;
 
         // get index
@@ -413,12 +1029,50 @@ impl SignalEnum {
     fn bits_required(&self) -> Option<u32> {
         match self {
             SignalEnum::Data { num_bits, .. } => *num_bits,
+            SignalEnum::Bus { range, .. } => Some(range.width()),
             // TODO: Follow aliases?
             SignalEnum::Alias { .. } => None,
         }
     }
 }
 
+/// The indices of the value changes at ``lsb_indxs`` and ``byte_lens``,
+/// ordered by time. Of several changes at the same time, only the last one
+/// is kept.
+fn chronological_order(
+    lsb_indxs: &[LsbIdxOfTmstmpValOnTmln],
+    byte_lens: &[u8],
+    tmstmps_encoded_as_u8s: &[u8],
+) -> Vec<usize> {
+    let times: Vec<BigUint> = lsb_indxs
+        .iter()
+        .zip(byte_lens)
+        .map(|(LsbIdxOfTmstmpValOnTmln(lsb_idx), byte_len)| {
+            let lsb_idx = *lsb_idx as usize;
+            BigUint::from_bytes_le(
+                &tmstmps_encoded_as_u8s[lsb_idx..lsb_idx + usize::from(*byte_len)],
+            )
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..times.len()).collect();
+    order.sort_by(|a, b| times[*a].cmp(&times[*b]));
+    // the sort is stable, so the last change at a given time is the last
+    // one of its run
+    let mut deduped: Vec<usize> = Vec::with_capacity(order.len());
+    for idx in order {
+        match deduped.last_mut() {
+            Some(last) if times[*last] == times[idx] => *last = idx,
+            _ => deduped.push(idx),
+        }
+    }
+    deduped
+}
+
+fn reorder<T: Clone>(values: &[T], order: &[usize]) -> Vec<T> {
+    order.iter().map(|idx| values[*idx].clone()).collect()
+}
+
 // Val and string query functions.
 // Function that take in a desired time on the timeline for a
 // specific signal and return a numerical or string value in a Result,
@@ -427,21 +1081,16 @@ impl SignalEnum {
     pub fn query_string_val_on_tmln(
         &self,
         desired_time: &BigUint,
-        tmstmps_encoded_as_u8s: &Vec<u8>,
-        all_signals: &Vec<SignalEnum>,
+        tmstmps_encoded_as_u8s: &[u8],
+        all_signals: &[SignalEnum],
     ) -> Result<QueryResult<String>, SignalErrors> {
-        let signal_idx = match self {
-            Self::Data { self_idx, .. } => {
-                let SignalIdx(idx) = self_idx;
-                *idx
-            }
-            Self::Alias {
-                name: _,
-                signal_alias,
-                path: _,
-            } => {
+        // a data signal may be a lazily loaded copy of the one in
+        // ``all_signals``, so we only go through ``all_signals`` for aliases
+        let signal = match self {
+            Self::Data { .. } | Self::Bus { .. } => self,
+            Self::Alias { signal_alias, .. } => {
                 let SignalIdx(idx) = signal_alias;
-                *idx
+                &all_signals[*idx]
             }
         };
 
@@ -451,21 +1100,21 @@ impl SignalEnum {
         // 2. the vector of indices into timeline where events occur
         //    for this signal
         // else we propagate Err(..).
-        let (string_vals, lsb_indxs_of_string_tmstmp_vals_on_tmln) = match &all_signals[signal_idx]
-        {
+        let (string_vals, lsb_indxs_of_string_tmstmp_vals_on_tmln) = match signal {
+            SignalEnum::Data { loaded: false, .. } => Err(SignalErrors::NotLoaded),
             SignalEnum::Data {
                 ref string_vals,
                 ref lsb_indxs_of_string_tmstmp_vals_on_tmln,
                 ..
             } => Ok((string_vals, lsb_indxs_of_string_tmstmp_vals_on_tmln)),
-            SignalEnum::Alias { .. } => Err(SignalErrors::PointsToAlias),
+            SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(SignalErrors::PointsToAlias),
         }?;
         // this signal should at least have some events, otherwise, trying to index into
         // an empty vector later on would fail
         if lsb_indxs_of_string_tmstmp_vals_on_tmln.is_empty() {
             return Ok(QueryResult {
                 current: None,
-                next: None
+                next: None,
             });
         }
 
@@ -561,21 +1210,16 @@ impl SignalEnum {
     pub fn query_num_val_on_tmln(
         &self,
         desired_time: &BigUint,
-        tmstmps_encoded_as_u8s: &Vec<u8>,
-        all_signals: &Vec<SignalEnum>,
+        tmstmps_encoded_as_u8s: &[u8],
+        all_signals: &[SignalEnum],
     ) -> Result<QueryResult<BigUint>, SignalErrors> {
-        let signal_idx = match self {
-            Self::Data { self_idx, .. } => {
-                let SignalIdx(idx) = self_idx;
-                *idx
-            }
-            Self::Alias {
-                name: _,
-                path: _,
-                signal_alias,
-            } => {
+        // a data signal may be a lazily loaded copy of the one in
+        // ``all_signals``, so we only go through ``all_signals`` for aliases
+        let signal = match self {
+            Self::Data { .. } | Self::Bus { .. } => self,
+            Self::Alias { signal_alias, .. } => {
                 let SignalIdx(idx) = signal_alias;
-                *idx
+                &all_signals[*idx]
             }
         };
 
@@ -587,13 +1231,21 @@ impl SignalEnum {
         // 3. the number of bytes per value for this signal
         // else we propagate Err(..).
         let (nums_encoded_as_fixed_width_le_u8, lsb_indxs_of_num_tmstmp_vals_on_tmln, num_bytes) =
-            match &all_signals[signal_idx] {
+            match signal {
+                SignalEnum::Data { loaded: false, .. } => Err(SignalErrors::NotLoaded),
                 SignalEnum::Data {
                     num_bytes,
                     ref nums_encoded_as_fixed_width_le_u8,
                     ref lsb_indxs_of_num_tmstmp_vals_on_tmln,
                     ..
                 } => {
+                    // strings have no numeric values at all
+                    if num_bytes.is_none() && lsb_indxs_of_num_tmstmp_vals_on_tmln.is_empty() {
+                        return Ok(QueryResult {
+                            current: None,
+                            next: None,
+                        });
+                    }
                     if num_bytes.is_none() {
                         return Err(SignalErrors::NoNumBytes);
                     }
@@ -603,14 +1255,16 @@ impl SignalEnum {
                         num_bytes,
                     ))
                 }
-                SignalEnum::Alias { .. } => Err(SignalErrors::PointsToAlias),
+                SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => {
+                    Err(SignalErrors::PointsToAlias)
+                }
             }?;
         // this signal should at least have some events, otherwise, trying to index into
         // an empty vector later on would fail
         if lsb_indxs_of_num_tmstmp_vals_on_tmln.is_empty() {
             return Ok(QueryResult {
                 current: None,
-                next: None
+                next: None,
             });
         }
 
@@ -707,9 +1361,9 @@ impl SignalEnum {
             });
         }
 
-        return Ok(QueryResult {
+        Ok(QueryResult {
             current: Some((left_time, left_val)),
             next: Some(right_time),
-        });
+        })
     }
 }
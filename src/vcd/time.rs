@@ -0,0 +1,247 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use num::{BigInt, BigUint, Integer, Signed, ToPrimitive, Zero};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The units a `$timescale` may be given in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum TimeUnit {
+    Fs,
+    Ps,
+    Ns,
+    Us,
+    Ms,
+    S,
+}
+
+impl TimeUnit {
+    /// Every unit, from the smallest to the largest.
+    pub const ALL: [TimeUnit; 6] = [
+        TimeUnit::Fs,
+        TimeUnit::Ps,
+        TimeUnit::Ns,
+        TimeUnit::Us,
+        TimeUnit::Ms,
+        TimeUnit::S,
+    ];
+
+    /// The number of femtoseconds in one of this unit.
+    pub fn femtoseconds(self) -> u64 {
+        match self {
+            TimeUnit::Fs => 1,
+            TimeUnit::Ps => 1_000,
+            TimeUnit::Ns => 1_000_000,
+            TimeUnit::Us => 1_000_000_000,
+            TimeUnit::Ms => 1_000_000_000_000,
+            TimeUnit::S => 1_000_000_000_000_000,
+        }
+    }
+
+    /// The unit written as ``unit``, which may be `us` or `μs` for
+    /// microseconds.
+    pub fn from_name(unit: &str) -> Option<TimeUnit> {
+        match unit {
+            "fs" => Some(TimeUnit::Fs),
+            "ps" => Some(TimeUnit::Ps),
+            "ns" => Some(TimeUnit::Ns),
+            "us" | "μs" => Some(TimeUnit::Us),
+            "ms" => Some(TimeUnit::Ms),
+            "s" => Some(TimeUnit::S),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeUnit::Fs => write!(f, "fs"),
+            TimeUnit::Ps => write!(f, "ps"),
+            TimeUnit::Ns => write!(f, "ns"),
+            TimeUnit::Us => write!(f, "μs"),
+            TimeUnit::Ms => write!(f, "ms"),
+            TimeUnit::S => write!(f, "s"),
+        }
+    }
+}
+
+/// The length of one tick of the timeline, such as `10 ps`. The standard
+/// only allows a ``magnitude`` of 1, 10 or 100.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TimeScale {
+    magnitude: u32,
+    unit: TimeUnit,
+}
+
+impl TimeScale {
+    /// The timescale of ``magnitude`` ``unit``s, unless ``magnitude`` isn't
+    /// one of 1, 10 or 100.
+    pub fn new(magnitude: u32, unit: TimeUnit) -> Option<TimeScale> {
+        matches!(magnitude, 1 | 10 | 100).then_some(TimeScale { magnitude, unit })
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        self.magnitude
+    }
+
+    pub fn unit(&self) -> TimeUnit {
+        self.unit
+    }
+
+    /// The number of femtoseconds in one tick.
+    pub fn femtoseconds(&self) -> u64 {
+        u64::from(self.magnitude) * self.unit.femtoseconds()
+    }
+
+    /// The time ``ticks`` ticks after time zero.
+    pub fn to_time(&self, ticks: &BigUint) -> Time {
+        Time::from_femtoseconds(BigInt::from(ticks * self.femtoseconds()))
+    }
+
+    /// The number of ticks in ``time``, rounded down to the tick ``time``
+    /// falls in. Returns ``None`` for negative times.
+    pub fn to_ticks(&self, time: &Time) -> Option<BigUint> {
+        time.femtoseconds
+            .div_floor(&BigInt::from(self.femtoseconds()))
+            .to_biguint()
+    }
+}
+
+impl fmt::Display for TimeScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.magnitude, self.unit)
+    }
+}
+
+/// A point in simulation time, kept exactly as a number of femtoseconds.
+///
+/// Times are displayed in the largest unit that keeps them at or above 1,
+/// such as `12.5 ns`, and can be parsed back from text like `3.2us` or
+/// `3.2 μs`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Time {
+    femtoseconds: BigInt,
+}
+
+impl Time {
+    pub fn from_femtoseconds(femtoseconds: BigInt) -> Time {
+        Time { femtoseconds }
+    }
+
+    pub fn femtoseconds(&self) -> &BigInt {
+        &self.femtoseconds
+    }
+
+    /// The time as a number of ``unit``s, which may lose precision.
+    pub fn as_unit(&self, unit: TimeUnit) -> f64 {
+        let (whole, rest) = self
+            .femtoseconds
+            .div_rem(&BigInt::from(unit.femtoseconds()));
+        // dividing the parts separately keeps the fraction exact for times
+        // too large to be represented in femtoseconds by an f64
+        let whole = whole.to_f64().unwrap_or(f64::NAN);
+        let rest = rest.to_f64().unwrap_or(f64::NAN);
+        whole + rest / unit.femtoseconds() as f64
+    }
+
+    pub fn as_seconds(&self) -> f64 {
+        self.as_unit(TimeUnit::S)
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magnitude = self.femtoseconds.abs();
+        let unit = TimeUnit::ALL
+            .into_iter()
+            .rev()
+            .find(|unit| magnitude >= BigInt::from(unit.femtoseconds()))
+            .unwrap_or(TimeUnit::S);
+
+        let (whole, rest) = magnitude.div_rem(&BigInt::from(unit.femtoseconds()));
+        if self.femtoseconds.is_negative() {
+            f.write_str("-")?;
+        }
+        write!(f, "{whole}")?;
+        if !rest.is_zero() {
+            // pad the fraction to the number of digits in a unit, and drop
+            // the trailing zeros
+            let digits = unit.femtoseconds().ilog10() as usize;
+            let fraction = format!("{rest:0>digits$}");
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        write!(f, " {unit}")
+    }
+}
+
+impl FromStr for Time {
+    type Err = ParseTimeError;
+
+    fn from_str(text: &str) -> Result<Time, ParseTimeError> {
+        let error = || ParseTimeError {
+            text: text.to_string(),
+        };
+
+        let trimmed = text.trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, trimmed),
+        };
+        let number_len = unsigned
+            .find(|chr: char| !chr.is_ascii_digit() && chr != '.')
+            .unwrap_or(unsigned.len());
+        let (number, unit) = unsigned.split_at(number_len);
+        let unit = TimeUnit::from_name(unit.trim()).ok_or_else(error)?;
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(error());
+        }
+        // the fraction can't be finer than a femtosecond
+        let digits = unit.femtoseconds().ilog10() as usize;
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > digits || !fraction.bytes().all(|chr| chr.is_ascii_digit()) {
+            return Err(error());
+        }
+
+        let whole = match whole {
+            "" => BigInt::zero(),
+            whole => whole.parse::<BigInt>().map_err(|_| error())?,
+        };
+        let fraction = match fraction {
+            "" => BigInt::zero(),
+            fraction => format!("{fraction:0<digits$}")
+                .parse::<BigInt>()
+                .map_err(|_| error())?,
+        };
+        let femtoseconds = whole * unit.femtoseconds() + fraction;
+
+        Ok(Time::from_femtoseconds(if negative {
+            -femtoseconds
+        } else {
+            femtoseconds
+        }))
+    }
+}
+
+/// The error for text that can't be read as a ``Time``.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimeError {
+    pub text: String,
+}
+
+impl fmt::Display for ParseTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid time `{}`, expected a number followed by one of fs, ps, ns, us, ms or s",
+            self.text
+        )
+    }
+}
+
+impl Error for ParseTimeError {}
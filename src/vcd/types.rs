@@ -4,44 +4,66 @@
 // This program is distributed under both the GPLV3 license
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
-use super::signal::{Signal, SignalEnum};
+use super::error::{Diagnostic, ParseError};
+use super::parse::lazy::LazyLoader;
+use super::signal::{Signal, SignalEnum, SignalValue};
+use super::time::{Time, TimeScale};
 use chrono::prelude::{DateTime, Utc};
-use num::BigUint;
-use std::fmt;
+use num::{BigInt, BigUint, Integer};
 
 #[derive(Debug, Clone)]
 pub struct Version(pub String);
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub enum Timescale {
-    Fs,
-    Ps,
-    Ns,
-    Us,
-    Ms,
-    S,
-    Unit,
+#[derive(Debug)]
+pub struct Metadata {
+    /// The `$date` of the dump. Dates without a time zone are taken to be
+    /// in UTC.
+    pub date: Option<DateTime<Utc>>,
+    /// The text of the `$date` section with runs of whitespace collapsed
+    /// into single spaces, which is kept even when ``date`` is ``None``
+    /// because the layout of the date isn't one we know of.
+    pub raw_date: Option<String>,
+    pub version: Option<Version>,
+    /// The length of a tick of the timeline, if the header gave one.
+    pub timescale: Option<TimeScale>,
+    /// The `$timezero` of the dump, which is the time of the timeline's
+    /// tick zero in ticks. Defaults to 0.
+    pub timezero: Option<i64>,
+    /// The comments and unknown sections of the header in the order they
+    /// appeared, including comments between top level scopes.
+    pub extra_sections: Vec<HeaderSection>,
 }
 
-impl fmt::Display for Timescale {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Timescale::Fs => write!(f, "fs"),
-            Timescale::Ps => write!(f, "ps"),
-            Timescale::Ns => write!(f, "ns"),
-            Timescale::Us => write!(f, "μs"),
-            Timescale::Ms => write!(f, "ms"),
-            Timescale::S => write!(f, "s"),
-            Timescale::Unit => write!(f, "unit"),
-        }
+impl Metadata {
+    /// The time at ``tick`` on the timeline, taking the `$timezero` into
+    /// account. Returns ``None`` for dumps without a `$timescale`.
+    pub fn time_at(&self, tick: &BigUint) -> Option<Time> {
+        let timescale = self.timescale?;
+        let tick = BigInt::from(tick.clone()) + self.timezero.unwrap_or(0);
+        Some(Time::from_femtoseconds(tick * timescale.femtoseconds()))
+    }
+
+    /// The tick on the timeline that ``time`` falls in, which can be
+    /// handed to ``Signal::query_val_on_tmln``. Returns ``None`` for dumps
+    /// without a `$timescale` and for times before tick zero.
+    pub fn tick_at(&self, time: &Time) -> Option<BigUint> {
+        let timescale = self.timescale?;
+        let ticks = time
+            .femtoseconds()
+            .div_floor(&BigInt::from(timescale.femtoseconds()));
+        (ticks - self.timezero.unwrap_or(0)).to_biguint()
     }
 }
 
-#[derive(Debug)]
-pub struct Metadata {
-    pub date: Option<DateTime<Utc>>,
-    pub version: Option<Version>,
-    pub timescale: (Option<u32>, Timescale),
+/// A section of the header that isn't parsed into a field of ``Metadata``.
+/// Texts have their runs of whitespace collapsed into single spaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderSection {
+    /// The text of a `$comment`.
+    Comment(String),
+    /// A section with a keyword we don't know of, such as a vendor
+    /// extension. The keyword includes its `$`.
+    Unknown { keyword: String, text: String },
 }
 
 // We do a lot of arena allocation in this codebase.
@@ -51,14 +73,131 @@ pub struct ScopeIdx(pub usize);
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SignalIdx(pub usize);
 
+/// The kind of a `$scope`. Besides the kinds of IEEE 1364, these include
+/// those that SystemVerilog and VHDL simulators such as GHDL write.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ScopeType {
+    Module,
+    Task,
+    Function,
+    Begin,
+    Fork,
+    Generate,
+    Interface,
+    Package,
+    Program,
+    Struct,
+    Union,
+    Class,
+    VhdlArchitecture,
+    VhdlProcedure,
+    VhdlFunction,
+    VhdlRecord,
+    VhdlProcess,
+    VhdlBlock,
+    VhdlForGenerate,
+    VhdlIfGenerate,
+    VhdlGenerate,
+    VhdlPackage,
+    /// A kind we don't know of, as it was written.
+    Other(String),
+}
+
+impl ScopeType {
+    /// The kind written as ``keyword`` in a `$scope`.
+    pub fn from_keyword(keyword: &str) -> ScopeType {
+        match keyword {
+            "module" => ScopeType::Module,
+            "task" => ScopeType::Task,
+            "function" => ScopeType::Function,
+            "begin" => ScopeType::Begin,
+            "fork" => ScopeType::Fork,
+            "generate" => ScopeType::Generate,
+            "interface" => ScopeType::Interface,
+            "package" => ScopeType::Package,
+            "program" => ScopeType::Program,
+            "struct" => ScopeType::Struct,
+            "union" => ScopeType::Union,
+            "class" => ScopeType::Class,
+            "vhdl_architecture" => ScopeType::VhdlArchitecture,
+            "vhdl_procedure" => ScopeType::VhdlProcedure,
+            "vhdl_function" => ScopeType::VhdlFunction,
+            "vhdl_record" => ScopeType::VhdlRecord,
+            "vhdl_process" => ScopeType::VhdlProcess,
+            "vhdl_block" => ScopeType::VhdlBlock,
+            "vhdl_for_generate" => ScopeType::VhdlForGenerate,
+            "vhdl_if_generate" => ScopeType::VhdlIfGenerate,
+            "vhdl_generate" => ScopeType::VhdlGenerate,
+            "vhdl_package" => ScopeType::VhdlPackage,
+            other => ScopeType::Other(other.to_string()),
+        }
+    }
+
+    /// The keyword the kind is written as in a `$scope`.
+    pub fn keyword(&self) -> &str {
+        match self {
+            ScopeType::Module => "module",
+            ScopeType::Task => "task",
+            ScopeType::Function => "function",
+            ScopeType::Begin => "begin",
+            ScopeType::Fork => "fork",
+            ScopeType::Generate => "generate",
+            ScopeType::Interface => "interface",
+            ScopeType::Package => "package",
+            ScopeType::Program => "program",
+            ScopeType::Struct => "struct",
+            ScopeType::Union => "union",
+            ScopeType::Class => "class",
+            ScopeType::VhdlArchitecture => "vhdl_architecture",
+            ScopeType::VhdlProcedure => "vhdl_procedure",
+            ScopeType::VhdlFunction => "vhdl_function",
+            ScopeType::VhdlRecord => "vhdl_record",
+            ScopeType::VhdlProcess => "vhdl_process",
+            ScopeType::VhdlBlock => "vhdl_block",
+            ScopeType::VhdlForGenerate => "vhdl_for_generate",
+            ScopeType::VhdlIfGenerate => "vhdl_if_generate",
+            ScopeType::VhdlGenerate => "vhdl_generate",
+            ScopeType::VhdlPackage => "vhdl_package",
+            ScopeType::Other(keyword) => keyword,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct Scope {
     pub(super) name: String,
+    // The signals that precede the first `$scope` are kept in a scope
+    // of type ``ScopeType::Module``.
+    pub(super) scope_type: ScopeType,
 
     pub(super) self_idx: ScopeIdx,
 
     pub(super) child_signals: Vec<SignalIdx>,
     pub(super) child_scopes: Vec<ScopeIdx>,
+    // The texts of the `$comment`s declared within this scope.
+    pub(super) comments: Vec<String>,
+}
+
+/// The simulation keywords that may appear among the value changes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DumpCommand {
+    /// `$dumpvars`, which lists the initial value of every signal.
+    DumpVars,
+    /// `$dumpall`, a checkpoint listing the current value of every signal.
+    DumpAll,
+    /// `$dumpon`, which resumes dumping after a `$dumpoff`.
+    DumpOn,
+    /// `$dumpoff`, which suspends dumping; the gap is reported by
+    /// ``VCD::dumpoff_intervals``.
+    DumpOff,
+}
+
+/// A stretch of time during which the simulator did not record value
+/// changes. An ``end`` of ``None`` means dumping was never resumed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DumpOffInterval {
+    pub start: BigUint,
+    pub end: Option<BigUint>,
 }
 
 #[derive(Debug)]
@@ -79,6 +218,18 @@ pub struct VCD {
     pub(super) all_scopes: Vec<Scope>,
     pub(super) root_scopes: Vec<ScopeIdx>,
     pub(super) largest_timestamp: Option<BigUint>,
+    // Every simulation keyword in the order it appeared, along with the
+    // time at which it appeared.
+    pub(super) dump_commands: Vec<(DumpCommand, BigUint)>,
+    // Every `$comment` among the value changes, along with the time at
+    // which it appeared.
+    pub(super) comments: Vec<(String, BigUint)>,
+    // The value changes found inside of `$dumpvars` blocks.
+    pub(super) initial_values: Vec<(SignalIdx, SignalValue)>,
+    // The problems a lenient parse recovered from.
+    pub(super) diagnostics: Vec<Diagnostic>,
+    // Decodes signals on demand for VCDs from ``parse_vcd_from_path_lazily``.
+    pub(super) lazy: Option<LazyLoader>,
 }
 
 impl VCD {
@@ -100,10 +251,19 @@ impl VCD {
         let scope = &self.all_scopes[idx];
         &scope.name
     }
+    pub fn scope_type_by_idx(&self, scope_idx: ScopeIdx) -> &ScopeType {
+        let ScopeIdx(idx) = scope_idx;
+        &self.all_scopes[idx].scope_type
+    }
+    /// The texts of the `$comment`s declared directly within a scope.
+    pub fn scope_comments_by_idx(&self, scope_idx: ScopeIdx) -> &[String] {
+        let ScopeIdx(idx) = scope_idx;
+        &self.all_scopes[idx].comments
+    }
     pub fn signal_from_signal_idx(&self, idx: SignalIdx) -> Signal<'_> {
         let SignalIdx(idx) = idx;
         let signal_enum = &self.all_signals[idx];
-        return Signal(signal_enum);
+        Signal(signal_enum)
     }
     /// We take in a Signal and attempt to de-alias that signal if it is of
     /// variant ``Signal::Alias``. If it is of variant ``Signal::Alias`` and points to
@@ -113,7 +273,7 @@ impl VCD {
     pub(super) fn dealiasing_signal_idx_to_signal_lookup_mut<'a>(
         &'a mut self,
         idx: &SignalIdx,
-    ) -> Result<&'a mut SignalEnum, String> {
+    ) -> Result<&'a mut SignalEnum, ParseError> {
         // get the signal pointed to be SignalIdx from the arena
         let SignalIdx(idx) = idx;
         let signal = &self.all_signals[*idx];
@@ -122,6 +282,11 @@ impl VCD {
         let signal_idx = match signal {
             SignalEnum::Data { self_idx, .. } => *self_idx,
             SignalEnum::Alias { signal_alias, .. } => *signal_alias,
+            SignalEnum::Bus { .. } => {
+                return Err(ParseError::internal(
+                    "a value change shouldn't point to an assembled bus",
+                ))
+            }
         };
 
         // Should now  point to Signal::Data variant, or else there's an error
@@ -129,16 +294,90 @@ impl VCD {
         let signal = self.all_signals.get_mut(idx).unwrap();
         match signal {
             SignalEnum::Data { .. } => Ok(signal),
-            SignalEnum::Alias { .. } => Err(format!(
-                "Error near {}:{}. A signal alias shouldn't \
-                 point to a signal alias.",
-                file!(),
-                line!()
+            SignalEnum::Alias { .. } | SignalEnum::Bus { .. } => Err(ParseError::internal(
+                "a signal alias shouldn't point to a signal alias",
             )),
         }
     }
 
+    /// Decodes the value changes of ``signals`` in one pass over the file,
+    /// if they haven't been yet. Only lazily parsed VCDs ever need to,
+    /// where querying a signal would otherwise decode it by itself.
+    pub fn load_signals(&self, signals: &[SignalIdx]) -> Result<(), ParseError> {
+        let real_idxs: Vec<SignalIdx> = signals
+            .iter()
+            .flat_map(|idx| self.signal_from_signal_idx(*idx).data_idxs(self))
+            .collect();
+        match &self.lazy {
+            Some(lazy) => lazy.load(self, &real_idxs),
+            None => Ok(()),
+        }
+    }
+
     pub fn max_timestamp(&self) -> &Option<BigUint> {
         &self.largest_timestamp
     }
+
+    /// Every `$dumpvars`, `$dumpall`, `$dumpon` and `$dumpoff` in the order
+    /// they appear in the file, along with the time at which they appear.
+    pub fn dump_commands(&self) -> &[(DumpCommand, BigUint)] {
+        &self.dump_commands
+    }
+
+    /// Every `$comment` among the value changes in the order they appear in
+    /// the file, along with the time at which they appear.
+    pub fn comments(&self) -> &[(String, BigUint)] {
+        &self.comments
+    }
+
+    /// The periods between a `$dumpoff` and the following `$dumpon`. The
+    /// intervals are only reported: no values are inserted for them, so
+    /// signal queries within these periods return whatever the dump itself
+    /// recorded, which is `x` only if the `$dumpoff` block lists `x` values.
+    pub fn dumpoff_intervals(&self) -> Vec<DumpOffInterval> {
+        let mut intervals: Vec<DumpOffInterval> = vec![];
+        for (command, time) in &self.dump_commands {
+            let dumping = intervals.last().is_none_or(|last| last.end.is_some());
+            match command {
+                DumpCommand::DumpOff if dumping => intervals.push(DumpOffInterval {
+                    start: time.clone(),
+                    end: None,
+                }),
+                DumpCommand::DumpOn if !dumping => {
+                    intervals.last_mut().unwrap().end = Some(time.clone())
+                }
+                _ => {}
+            }
+        }
+        intervals
+    }
+
+    /// Whether the simulator was recording value changes at ``time``.
+    pub fn is_dumping_at(&self, time: &BigUint) -> bool {
+        !self.dumpoff_intervals().iter().any(|interval| {
+            interval.start <= *time && interval.end.as_ref().is_none_or(|end| time < end)
+        })
+    }
+
+    /// The times of the `$dumpall` checkpoints. The values listed in a
+    /// checkpoint show up as regular value changes, even though they need
+    /// not differ from the values before the checkpoint.
+    pub fn dumpall_timestamps(&self) -> Vec<BigUint> {
+        self.dump_commands
+            .iter()
+            .filter(|(command, _)| *command == DumpCommand::DumpAll)
+            .map(|(_, time)| time.clone())
+            .collect()
+    }
+
+    /// The values listed in `$dumpvars` blocks, in the order they appear.
+    pub fn initial_values(&self) -> &[(SignalIdx, SignalValue)] {
+        &self.initial_values
+    }
+
+    /// The problems found in the input, in the order they were found. Only
+    /// ever filled in when parsing with ``ParseOptions::lenient``.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
 }
@@ -2,6 +2,9 @@
 // This program is distributed under both the GPLV3 license
 // and the YEHOWSHUA license, both of which can be found at
 // the root of the folder containing the sources for this program.
+// `OtherValue`'s payload is only read through the Debug impl when we
+// format error messages.
+#[allow(dead_code)]
 #[derive(Debug)]
 pub(super) enum BinaryParserErrTypes {
     XValue,
@@ -0,0 +1,159 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd_from_bytes_with_options, parse_vcd_header, BigUint, BitIndex, BitRange, ParseOptions,
+    SignalFilter, SignalValue, VCD,
+};
+
+mod common;
+
+fn dump() -> String {
+    let vars = [
+        "wire 1 ! data [3]",
+        "wire 1 \" data [2]",
+        "wire 1 # data [1]",
+        "wire 1 $ data [0]",
+        "wire 1 % gap[4]",
+        "wire 1 & gap[2]",
+        "reg 8 ' count [7:0]",
+    ];
+    common::dump(
+        Some("1 ns"),
+        &vars,
+        "#0\n1!\n0\"\n1#\nb101 '\n#10\n0$\n#20\nx#\n",
+    )
+}
+
+fn parse(options: ParseOptions) -> VCD {
+    parse_vcd_from_bytes_with_options(dump().as_bytes(), &options).unwrap()
+}
+
+fn with_buses() -> ParseOptions {
+    ParseOptions {
+        assemble_buses: true,
+        ..ParseOptions::default()
+    }
+}
+
+fn names(vcd: &VCD) -> Vec<String> {
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    vcd.get_children_signal_idxs(top)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx).name_with_index())
+        .collect()
+}
+
+fn value(vcd: &VCD, name: &str, time: u32) -> Option<(BigUint, SignalValue)> {
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    let signal = vcd
+        .get_children_signal_idxs(top)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx))
+        .find(|signal| signal.name_with_index() == name)
+        .unwrap();
+    signal
+        .query_val_on_tmln(&BigUint::from(time), vcd)
+        .unwrap()
+        .current
+}
+
+#[test]
+fn bit_indices_are_parsed() {
+    let range = BitRange { msb: 7, lsb: 0 };
+    assert_eq!(BitIndex::parse("[7:0]"), Some(BitIndex::Range(range)));
+    assert_eq!(BitIndex::parse("[-1]"), Some(BitIndex::Bit(-1)));
+    assert_eq!(BitIndex::parse("[0:3]").map(|index| index.width()), Some(4));
+    assert_eq!(BitIndex::parse("[a]"), None);
+    assert_eq!(BitIndex::parse("7:0"), None);
+    assert_eq!(BitIndex::Range(range).to_string(), "[7:0]");
+
+    let vcd = parse(ParseOptions::default());
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    let indices: Vec<Option<BitIndex>> = vcd
+        .get_children_signal_idxs(top)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx).bit_index())
+        .collect();
+    assert_eq!(indices[0], Some(BitIndex::Bit(3)));
+    assert_eq!(indices[4], Some(BitIndex::Bit(4)));
+    assert_eq!(indices[6], Some(BitIndex::Range(range)));
+}
+
+#[test]
+fn buses_are_only_assembled_when_asked_for() {
+    assert_eq!(names(&parse(ParseOptions::default())).len(), 7);
+
+    // the bits of `gap` aren't contiguous
+    let names = names(&parse(with_buses()));
+    assert_eq!(names.len(), 8);
+    assert_eq!(names[7], "data [3:0]");
+}
+
+#[test]
+fn buses_are_assembled_when_finishing_a_header() {
+    let dump = dump();
+    let header = parse_vcd_header(dump.as_bytes()).unwrap();
+    let vcd = header.finish_with_options(&with_buses()).unwrap();
+
+    let parsed = parse(with_buses());
+    assert_eq!(names(&vcd), names(&parsed));
+    assert_eq!(
+        value(&vcd, "data [3:0]", 10),
+        value(&parsed, "data [3:0]", 10)
+    );
+}
+
+#[test]
+fn buses_combine_the_values_of_their_bits() {
+    let vcd = parse(with_buses());
+
+    // bit 0 has no value before #10
+    assert_eq!(
+        value(&vcd, "data [3:0]", 5),
+        Some((BigUint::from(0u8), SignalValue::String("101x".to_string())))
+    );
+    assert_eq!(
+        value(&vcd, "data [3:0]", 10),
+        Some((
+            BigUint::from(10u8),
+            SignalValue::BigUint(BigUint::from(0b1010u8))
+        ))
+    );
+    assert_eq!(
+        value(&vcd, "data [3:0]", 25),
+        Some((BigUint::from(20u8), SignalValue::String("10x0".to_string())))
+    );
+}
+
+#[test]
+fn filtering_a_bus_loads_its_bits() {
+    let options = ParseOptions {
+        signal_filter: Some(SignalFilter::from_predicate(|signal| {
+            signal.bus_bits().is_some()
+        })),
+        ..with_buses()
+    };
+    let vcd = parse(options);
+
+    assert_eq!(
+        value(&vcd, "data [3:0]", 10).map(|(_, value)| value),
+        Some(SignalValue::BigUint(BigUint::from(0b1010u8)))
+    );
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    let loaded: Vec<bool> = vcd
+        .get_children_signal_idxs(top)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx).is_loaded(&vcd))
+        .collect();
+    assert_eq!(loaded, [true, true, true, true, false, false, false, true]);
+}
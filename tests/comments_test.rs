@@ -0,0 +1,84 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes_with_options, BigUint, HeaderSection, ParseOptions,
+};
+
+const DUMP: &str = "$comment generated by\n  a testbench $end\n\
+    $date today $end\n\
+    $vendor_extension key = value $end\n\
+    $timescale 1 ns $end\n\
+    $scope module top $end\n\
+    $comment about top $end\n\
+    $var wire 1 ! a $end\n\
+    $upscope $end\n\
+    $comment between trees $end\n\
+    $scope module other $end\n\
+    $var wire 1 \" b $end\n\
+    $upscope $end\n\
+    $enddefinitions $end\n\
+    #0\n\
+    $comment #5 1! looks like a value change $end\n\
+    0! 0\"\n\
+    #10\n\
+    1!\n\
+    $comment done $end\n";
+
+#[test]
+fn header_sections_are_kept_in_order() {
+    let vcd = parse_vcd(DUMP.as_bytes()).unwrap();
+    assert_eq!(
+        vcd.metadata.extra_sections,
+        vec![
+            HeaderSection::Comment("generated by a testbench".to_string()),
+            HeaderSection::Unknown {
+                keyword: "$vendor_extension".to_string(),
+                text: "key = value".to_string(),
+            },
+            HeaderSection::Comment("between trees".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn scope_and_value_change_comments_are_attached() {
+    let vcd = parse_vcd(DUMP.as_bytes()).unwrap();
+    let [top, other] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected two root scopes");
+    };
+    assert_eq!(vcd.scope_comments_by_idx(top), ["about top"]);
+    assert!(vcd.scope_comments_by_idx(other).is_empty());
+    assert_eq!(
+        vcd.comments(),
+        [
+            (
+                "#5 1! looks like a value change".to_string(),
+                BigUint::from(0u32)
+            ),
+            ("done".to_string(), BigUint::from(10u32)),
+        ]
+    );
+}
+
+#[test]
+fn parallel_parses_keep_comments_like_sequential_ones() {
+    let mut dump = DUMP.to_string();
+    for time in 11..3000u32 {
+        dump.push_str(&format!("#{time}\n{}!\n", time % 2));
+        if time % 100 == 0 {
+            dump.push_str(&format!("$comment at {time} $end\n"));
+        }
+    }
+
+    let sequential =
+        parse_vcd_from_bytes_with_options(dump.as_bytes(), &Default::default()).unwrap();
+    let options = ParseOptions {
+        threads: 4,
+        ..Default::default()
+    };
+    let parallel = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+    assert_eq!(sequential.comments().len(), 31);
+    assert_eq!(format!("{sequential:?}"), format!("{parallel:?}"));
+}
@@ -0,0 +1,45 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+
+// Helpers shared by the tests, which each use only some of them.
+use fastwave_backend::{BigUint, SignalErrors, SignalIdx, SignalValue, VCD};
+
+/// A dump that declares ``vars``, such as `wire 1 ! clk`, one per line in
+/// a module `top`, followed by ``events``.
+#[allow(dead_code)]
+pub fn dump(timescale: Option<&str>, vars: &[&str], events: &str) -> String {
+    let mut dump = String::new();
+    if let Some(timescale) = timescale {
+        dump += &format!("$timescale {timescale} $end\n");
+    }
+    dump += "$scope module top $end\n";
+    for var in vars {
+        dump += &format!("$var {var} $end\n");
+    }
+    dump += "$upscope $end\n$enddefinitions $end\n";
+    dump + events
+}
+
+/// The value of ``signal_idx`` at ``time``, or why it can't be queried.
+#[allow(dead_code)]
+pub fn try_value_at(
+    vcd: &VCD,
+    signal_idx: usize,
+    time: u32,
+) -> Result<Option<SignalValue>, SignalErrors> {
+    vcd.signal_from_signal_idx(SignalIdx(signal_idx))
+        .query_val_on_tmln(&BigUint::from(time), vcd)
+        .map(|result| result.current.map(|(_, value)| value))
+}
+
+#[allow(dead_code)]
+pub fn value_at(vcd: &VCD, signal_idx: usize, time: u32) -> Option<SignalValue> {
+    try_value_at(vcd, signal_idx, time).unwrap()
+}
+
+#[allow(dead_code)]
+pub fn num(val: u32) -> Option<SignalValue> {
+    Some(SignalValue::BigUint(val.into()))
+}
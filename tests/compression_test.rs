@@ -0,0 +1,44 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::parse_vcd_from_path;
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "bzip2"))]
+const UNCOMPRESSED: &str = "./tests/vcd-files/amaranth/up_counter.vcd";
+
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "bzip2"))]
+fn assert_same_as_uncompressed(compressed: &str) {
+    let expected = parse_vcd_from_path(UNCOMPRESSED).unwrap();
+    let decompressed = parse_vcd_from_path(compressed).unwrap();
+    assert_eq!(format!("{expected:?}"), format!("{decompressed:?}"));
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn parse_gzip_compressed() {
+    assert_same_as_uncompressed("./tests/vcd-files/compressed/up_counter.vcd.gz");
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn parse_zstd_compressed() {
+    assert_same_as_uncompressed("./tests/vcd-files/compressed/up_counter.vcd.zst");
+}
+
+#[test]
+#[cfg(feature = "bzip2")]
+fn parse_bzip2_compressed() {
+    assert_same_as_uncompressed("./tests/vcd-files/compressed/up_counter.vcd.bz2");
+}
+
+#[test]
+#[cfg(not(feature = "gzip"))]
+fn gzip_without_feature_is_an_error() {
+    let err = parse_vcd_from_path("./tests/vcd-files/compressed/up_counter.vcd.gz").unwrap_err();
+    assert_eq!(
+        err,
+        fastwave_backend::ParseError::UnsupportedCompression { format: "gzip" }
+    );
+    assert!(err.to_string().contains("`gzip` feature"), "{err}");
+}
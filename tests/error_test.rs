@@ -0,0 +1,90 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes, parse_vcd_from_bytes_with_options, Cursor, ParseError,
+    ParseOptions,
+};
+
+mod common;
+
+/// ``events`` after a header declaring `a`.
+fn with_header(events: &str) -> String {
+    common::dump(Some("1ns"), &["wire 1 ! a"], events)
+}
+
+/// Parses ``dump`` both from a stream and from memory, checking that both
+/// fail the same way.
+fn parse_error(dump: &str) -> ParseError {
+    let streamed = parse_vcd(dump.as_bytes()).unwrap_err();
+    let in_memory = parse_vcd_from_bytes(dump.as_bytes()).unwrap_err();
+    assert_eq!(streamed, in_memory);
+    in_memory
+}
+
+#[test]
+fn undeclared_identifier_points_at_the_value_change() {
+    let dump = with_header("#0\n1!\n#10\n  0!  1?\n");
+    let error = parse_error(&dump);
+    let byte_offset = dump.find("1?").unwrap();
+    assert_eq!(
+        error,
+        ParseError::UndeclaredIdentifier {
+            identifier: "?".to_string(),
+            cursor: Cursor {
+                line: 9,
+                column: 7,
+                byte_offset,
+            },
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "undeclared identifier `?` at line 9, column 7"
+    );
+}
+
+#[test]
+fn errors_in_later_chunks_have_the_same_cursor_when_parsed_in_parallel() {
+    let mut dump = with_header("");
+    for time in 0..1000 {
+        dump.push_str(&format!("#{time}\n{}!\n", time % 2));
+    }
+    dump.push_str("#1000 b1 !\n#x\n");
+
+    let sequential = parse_vcd_from_bytes(dump.as_bytes()).unwrap_err();
+    let options = ParseOptions {
+        threads: 4,
+        ..Default::default()
+    };
+    let parallel = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap_err();
+    assert_eq!(sequential, parallel);
+    assert!(matches!(
+        sequential,
+        ParseError::BadTimestamp { ref timestamp, cursor }
+            if timestamp == "#x" && cursor.line == 2007 && cursor.column == 1
+    ));
+}
+
+#[test]
+fn header_errors_are_reported_with_their_location() {
+    let error = parse_error("$scope module top $end\n$var $wyre 1 ! a $end\n");
+    assert!(matches!(
+        error,
+        ParseError::UnknownKeyword { ref keyword, cursor, .. }
+            if keyword == "$wyre" && cursor.line == 2 && cursor.column == 6
+    ));
+
+    let error = parse_error("$scope module top $end\n$var wire 1 ! a");
+    assert_eq!(
+        error,
+        ParseError::UnexpectedEof {
+            cursor: Cursor {
+                line: 2,
+                column: 16,
+                byte_offset: 38,
+            },
+        }
+    );
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, validate_vcd, BigUint, DumpCommand, PortDirection, PortValue, SignalType,
+    SignalValue, VCD,
+};
+
+const DUMP: &str = "$date today $end\n\
+    $timescale 1 ns $end\n\
+    $scope module top $end\n\
+    $var port 1 <0 clk $end\n\
+    $var port [3:0] <1 data $end\n\
+    $upscope $end\n\
+    $enddefinitions $end\n\
+    #0\n\
+    $dumpports\n\
+    pD 6 0 <0\n\
+    pZZZZ 0 0 <1\n\
+    $end\n\
+    #10\n\
+    pU 0 6 <0\n\
+    pLHXT 6 6 <1\n\
+    #20\n\
+    p0 7 7 <0\n\
+    $vcdclose #30 $end\n";
+
+fn port(vcd: &VCD, name: &str, time: u32) -> PortValue {
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    let signal = vcd
+        .get_children_signal_idxs(top)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx))
+        .find(|signal| signal.name() == name)
+        .unwrap();
+    assert_eq!(signal.signal_type(), Some(&SignalType::Port));
+    match signal
+        .query_val_on_tmln(&BigUint::from(time), vcd)
+        .unwrap()
+        .current
+        .unwrap()
+        .1
+    {
+        SignalValue::Port(value) => value,
+        value => panic!("expected a port value, found {value:?}"),
+    }
+}
+
+#[test]
+fn ports_carry_their_states_and_strengths() {
+    let vcd = parse_vcd(DUMP.as_bytes()).unwrap();
+
+    let clk = port(&vcd, "clk", 10);
+    assert_eq!(
+        (clk.states.as_str(), clk.strength0, clk.strength1),
+        ("U", 0, 6)
+    );
+    assert_eq!(port(&vcd, "clk", 25).to_string(), "0 7 7");
+
+    let data = port(&vcd, "data", 15);
+    assert_eq!(data.states, "LHXT");
+    assert_eq!(
+        data.directions().collect::<Vec<_>>(),
+        vec![PortDirection::Output; 4]
+    );
+    assert_eq!(
+        port(&vcd, "data", 0).directions().next(),
+        Some(PortDirection::Input)
+    );
+
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    let data = vcd.signal_from_signal_idx(vcd.get_children_signal_idxs(top)[1]);
+    assert_eq!(data.num_bits(), Some(4));
+    assert_eq!(data.index(), Some("[3:0]".to_string()));
+
+    assert_eq!(
+        vcd.dump_commands()[0],
+        (DumpCommand::DumpVars, BigUint::from(0u32))
+    );
+    assert_eq!(vcd.initial_values().len(), 2);
+}
+
+#[test]
+fn extended_dumps_conform() {
+    let diagnostics = validate_vcd(DUMP.as_bytes()).unwrap();
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+    let bad = DUMP.replace("pU 0 6", "pQ 0 9");
+    let diagnostics = validate_vcd(bad.as_bytes()).unwrap();
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].message(), "invalid value `pQ 0 9`");
+    assert!(parse_vcd(bad.as_bytes()).is_err());
+}
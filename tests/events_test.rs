@@ -0,0 +1,177 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes, parse_vcd_from_bytes_with_options, BigUint, DumpCommand,
+    DumpOffInterval, ParseOptions, SignalIdx, SignalValue,
+};
+
+const SINGLE_LINE_HEADER: &str = "$timescale 1ns $end $scope module top $end \
+    $var wire 1 ! a $end $var wire 1 \" b $end $var wire 3 # c $end \
+    $upscope $end $enddefinitions $end";
+
+mod common;
+use common::{num, value_at};
+
+#[test]
+fn single_line_dump() {
+    let dump =
+        format!("{SINGLE_LINE_HEADER} #0 $dumpvars 0! 1\" b000 # $end #10 1! 0\" b101 # #20 0!");
+    let vcd = parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    assert_eq!(value_at(&vcd, 0, 0), num(0));
+    assert_eq!(value_at(&vcd, 1, 0), num(1));
+    assert_eq!(value_at(&vcd, 2, 0), num(0));
+
+    assert_eq!(value_at(&vcd, 0, 10), num(1));
+    assert_eq!(value_at(&vcd, 1, 10), num(0));
+    assert_eq!(value_at(&vcd, 2, 10), num(5));
+
+    assert_eq!(value_at(&vcd, 0, 20), num(0));
+    assert_eq!(vcd.max_timestamp(), &Some(BigUint::from(20u32)));
+}
+
+#[test]
+fn dumpvars_values_on_keyword_line() {
+    let dump = format!("{SINGLE_LINE_HEADER}\n#0\n$dumpvars 1! 0\" b11 #\n$end\n#3\n0!\n");
+    let vcd = parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    assert_eq!(value_at(&vcd, 0, 0), num(1));
+    assert_eq!(value_at(&vcd, 1, 0), num(0));
+    assert_eq!(value_at(&vcd, 2, 0), num(3));
+    assert_eq!(value_at(&vcd, 0, 3), num(0));
+}
+
+#[test]
+fn long_runs_of_blank_lines() {
+    let blank_lines = "\n".repeat(25);
+    let dump = format!("{SINGLE_LINE_HEADER}\n#0\n0!\n{blank_lines}#5\n1!\n");
+
+    for vcd in [
+        parse_vcd(dump.as_bytes()).unwrap(),
+        parse_vcd_from_bytes(dump.as_bytes()).unwrap(),
+    ] {
+        assert_eq!(vcd.max_timestamp(), &Some(BigUint::from(5u32)));
+        assert_eq!(value_at(&vcd, 0, 5), num(1));
+    }
+}
+
+#[test]
+fn comments_in_value_changes_are_skipped() {
+    let dump = format!(
+        "{SINGLE_LINE_HEADER} #0 0! 0\" b0 # \
+         #5 $comment 1! b111 # all values below are dumped $end 1\" #9 1!"
+    );
+    let vcd = parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    assert_eq!(value_at(&vcd, 0, 5), num(0));
+    assert_eq!(value_at(&vcd, 1, 5), num(1));
+    assert_eq!(value_at(&vcd, 2, 5), num(0));
+    assert_eq!(value_at(&vcd, 0, 9), num(1));
+}
+
+fn string(val: &str) -> Option<SignalValue> {
+    Some(SignalValue::String(val.to_string()))
+}
+
+#[test]
+fn simulation_keywords_are_recorded() {
+    let dump = format!(
+        "{SINGLE_LINE_HEADER}\n#0\n$dumpvars\n0!\nx\"\nb1 #\n$end\n\
+         #10\n$dumpoff\nx!\nx\"\nbxxx #\n$end\n\
+         #20\n$dumpon\n1!\n0\"\nb1 #\n$end\n\
+         #30\n$dumpall\n1!\n0\"\nb1 #\n$end\n\
+         #40\n$dumpoff\nx!\nx\"\nbxxx #\n$end\n"
+    );
+    let vcd = parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    let time = |t: u32| BigUint::from(t);
+    assert_eq!(
+        vcd.dump_commands(),
+        &[
+            (DumpCommand::DumpVars, time(0)),
+            (DumpCommand::DumpOff, time(10)),
+            (DumpCommand::DumpOn, time(20)),
+            (DumpCommand::DumpAll, time(30)),
+            (DumpCommand::DumpOff, time(40)),
+        ]
+    );
+    assert_eq!(
+        vcd.dumpoff_intervals(),
+        vec![
+            DumpOffInterval {
+                start: time(10),
+                end: Some(time(20)),
+            },
+            DumpOffInterval {
+                start: time(40),
+                end: None,
+            },
+        ]
+    );
+    assert!(vcd.is_dumping_at(&time(5)));
+    assert!(!vcd.is_dumping_at(&time(15)));
+    assert!(vcd.is_dumping_at(&time(20)));
+    assert!(!vcd.is_dumping_at(&time(100)));
+    assert_eq!(vcd.dumpall_timestamps(), vec![time(30)]);
+
+    let initial_values: Vec<_> = vcd
+        .initial_values()
+        .iter()
+        .map(|(idx, val)| (idx.0, Some(val.clone())))
+        .collect();
+    assert_eq!(
+        initial_values,
+        vec![(0, num(0)), (1, string("x")), (2, num(1))]
+    );
+
+    assert_eq!(value_at(&vcd, 0, 15), string("x"));
+    assert_eq!(value_at(&vcd, 2, 15), string("xxx"));
+    assert_eq!(value_at(&vcd, 0, 25), num(1));
+}
+
+#[test]
+fn short_values_are_left_extended() {
+    let dump = format!(
+        "{SINGLE_LINE_HEADER} #0 $dumpvars bz # $end #10 bx1 # #20 b1z # #30 b10 # #40 bX #"
+    );
+    let vcd = parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    assert_eq!(value_at(&vcd, 2, 0), string("zzz"));
+    assert_eq!(value_at(&vcd, 2, 10), string("xx1"));
+    assert_eq!(value_at(&vcd, 2, 20), string("01z"));
+    assert_eq!(value_at(&vcd, 2, 30), num(2));
+    assert_eq!(value_at(&vcd, 2, 40), string("xxx"));
+    assert_eq!(
+        vcd.initial_values(),
+        &[(SignalIdx(2), SignalValue::String("zzz".to_string()))]
+    );
+}
+
+#[test]
+fn parallel_parse_of_single_line_dump() {
+    // `#` is also used as an identifier code here, so chunks must not be
+    // split on it
+    let mut dump = SINGLE_LINE_HEADER.to_string();
+    for time in 0..2000u32 {
+        dump.push_str(&format!(" #{time} {}! b{:b} #", time % 2, time % 8));
+        if time % 100 == 0 {
+            dump.push_str(" $comment #1 #2 $end");
+        }
+        if time % 250 == 0 {
+            dump.push_str(" $dumpall 1\" $end");
+        }
+    }
+
+    let sequential = parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+    for threads in [2, 5, 16] {
+        let options = ParseOptions {
+            threads,
+            ..Default::default()
+        };
+        let parallel = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+        assert_eq!(format!("{sequential:?}"), format!("{parallel:?}"));
+    }
+    assert_eq!(value_at(&sequential, 2, 1234), num(1234 % 8));
+}
@@ -0,0 +1,93 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd_from_bytes, parse_vcd_from_bytes_with_options, parse_vcd_header, ParseOptions,
+    SignalErrors, SignalFilter, SignalIdx,
+};
+
+// `top.cpu.alias` shares its identifier code with `top.mem.data`
+const DUMP: &str = "$timescale 1ns $end $scope module top $end \
+    $scope module cpu $end $var wire 1 ! clk $end $var wire 8 \" pc $end \
+    $var wire 8 # alias $end $upscope $end \
+    $scope module mem $end $var wire 8 # data $end $upscope $end \
+    $upscope $end $enddefinitions $end \
+    #0 0! b0 \" b1 # #5 1! b100 \" b11 # #10 0! bx \" b111 #";
+
+mod common;
+use common::{num, try_value_at as value_at};
+
+fn with_filter(filter: SignalFilter) -> ParseOptions {
+    ParseOptions {
+        signal_filter: Some(filter),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn globs_select_signals() {
+    let full = parse_vcd_from_bytes(DUMP.as_bytes()).unwrap();
+    let options = with_filter(SignalFilter::from_globs(["top.cpu.p?"]));
+    let vcd = parse_vcd_from_bytes_with_options(DUMP.as_bytes(), &options).unwrap();
+
+    for time in [0, 5, 10] {
+        assert_eq!(
+            value_at(&vcd, 1, time).unwrap(),
+            value_at(&full, 1, time).unwrap()
+        );
+    }
+    assert!(matches!(value_at(&vcd, 0, 5), Err(SignalErrors::NotLoaded)));
+    assert!(matches!(value_at(&vcd, 2, 5), Err(SignalErrors::NotLoaded)));
+    assert!(!vcd.signal_from_signal_idx(SignalIdx(3)).is_loaded(&vcd));
+}
+
+#[test]
+fn selecting_an_alias_loads_the_aliased_signal() {
+    let options = with_filter(SignalFilter::from_globs(["*.mem.*"]));
+    let vcd = parse_vcd_from_bytes_with_options(DUMP.as_bytes(), &options).unwrap();
+    assert!(vcd.signal_from_signal_idx(SignalIdx(3)).is_loaded(&vcd));
+    assert_eq!(value_at(&vcd, 2, 5).unwrap(), num(3));
+    assert_eq!(value_at(&vcd, 2, 10).unwrap(), num(7));
+    assert!(matches!(value_at(&vcd, 1, 5), Err(SignalErrors::NotLoaded)));
+}
+
+#[test]
+fn predicate_selects_signals() {
+    let options = with_filter(SignalFilter::from_predicate(|signal| {
+        signal.num_bits() == Some(1)
+    }));
+    for threads in [1, 3] {
+        let options = ParseOptions {
+            threads,
+            ..options.clone()
+        };
+        let vcd = parse_vcd_from_bytes_with_options(DUMP.as_bytes(), &options).unwrap();
+        assert_eq!(value_at(&vcd, 0, 7).unwrap(), num(1));
+        assert!(matches!(value_at(&vcd, 1, 7), Err(SignalErrors::NotLoaded)));
+    }
+}
+
+#[test]
+fn header_can_be_finished_with_a_filter() {
+    let header = parse_vcd_header(DUMP.as_bytes()).unwrap();
+    let options = with_filter(SignalFilter::from_globs(["top.cpu.clk"]));
+    let vcd = header.finish_with_options(&options).unwrap();
+
+    assert!(vcd.signal_from_signal_idx(SignalIdx(0)).is_loaded(&vcd));
+    assert!(!vcd.signal_from_signal_idx(SignalIdx(1)).is_loaded(&vcd));
+}
+
+#[test]
+fn stars_match_across_scopes() {
+    let options = with_filter(SignalFilter::from_globs(["top.*"]));
+    let vcd = parse_vcd_from_bytes_with_options(DUMP.as_bytes(), &options).unwrap();
+    for idx in 0..4 {
+        assert!(vcd.signal_from_signal_idx(SignalIdx(idx)).is_loaded(&vcd));
+    }
+
+    let options = with_filter(SignalFilter::from_globs(["*clk"]));
+    let vcd = parse_vcd_from_bytes_with_options(DUMP.as_bytes(), &options).unwrap();
+    assert!(vcd.signal_from_signal_idx(SignalIdx(0)).is_loaded(&vcd));
+    assert!(!vcd.signal_from_signal_idx(SignalIdx(1)).is_loaded(&vcd));
+}
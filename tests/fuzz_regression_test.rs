@@ -0,0 +1,128 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+
+// Inputs that used to make the parser panic.
+use std::io::{self, Read};
+
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes, parse_vcd_from_bytes_with_options, validate_vcd, ParseError,
+    ParseOptions, TimestampPolicy,
+};
+
+mod common;
+
+/// ``events`` after a header declaring `clk`.
+fn with_header(events: &str) -> String {
+    common::dump(None, &["wire 1 ! clk"], events)
+}
+
+#[test]
+fn words_may_start_with_a_multibyte_letter() {
+    let dump = with_header("#0\nédition\n1!\n#5\n0!\n");
+    assert!(parse_vcd(dump.as_bytes()).is_ok());
+    assert!(parse_vcd_from_bytes(dump.as_bytes()).is_ok());
+    assert!(validate_vcd(dump.as_bytes()).is_ok());
+
+    // the words of a dropped block are skipped on a different path
+    let dump = with_header("#5\n1!\n#0\nédition\n0!\n#10\n");
+    let options = ParseOptions {
+        timestamp_policy: TimestampPolicy::Drop,
+        ..ParseOptions::default()
+    };
+    assert!(parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).is_ok());
+}
+
+#[test]
+fn words_that_arent_utf8_are_replaced() {
+    let mut dump = b"$comment caf\xe9 $end\n\
+        $scope module top $end\n\
+        $var wire 1 ! clk\xff $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n\
+        1!\n\
+        \xfe\n"
+        .to_vec();
+    dump.extend_from_slice(b"#5\n0!\n");
+
+    for vcd in [parse_vcd(&dump[..]), parse_vcd_from_bytes(&dump)] {
+        let vcd = vcd.unwrap();
+        let top = vcd.root_scopes_by_idx()[0];
+        let clk = vcd.signal_from_signal_idx(vcd.get_children_signal_idxs(top)[0]);
+        assert_eq!(clk.name(), "\u{FFFD}");
+    }
+    assert!(validate_vcd(&dump[..]).is_ok());
+}
+
+/// Reads ``bytes`` and then fails.
+struct FailingReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Read for FailingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.bytes.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the pipe broke"));
+        }
+        let len = self.bytes.len().min(buf.len());
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        self.bytes = &self.bytes[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn read_errors_are_returned() {
+    let dump = with_header("#0\n1!\n");
+    for bytes in [&dump.as_bytes()[..20], dump.as_bytes()] {
+        let error = parse_vcd(FailingReader { bytes }).unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ParseError::Io {
+                    kind: io::ErrorKind::BrokenPipe,
+                    ..
+                }
+            ),
+            "{error:?}"
+        );
+        assert!(validate_vcd(FailingReader { bytes }).is_err());
+    }
+}
+
+#[test]
+fn ranges_may_span_every_bit_index() {
+    let dump = "$scope module top $end\n\
+        $var port [9223372036854775807:-9223372036854775808] <0 x $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n";
+    // the width saturates, which is then far too wide to hold any value
+    for vcd in [
+        parse_vcd(dump.as_bytes()),
+        parse_vcd_from_bytes(dump.as_bytes()),
+    ] {
+        assert!(matches!(vcd, Err(ParseError::LimitExceeded { .. })));
+    }
+    assert!(validate_vcd(dump.as_bytes()).is_ok());
+}
+
+#[test]
+fn bits_may_sit_at_the_last_bit_index() {
+    let dump = "$scope module top $end\n\
+        $var wire 1 ! a[9223372036854775807] $end\n\
+        $var wire 1 \" a[9223372036854775807] $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n\
+        1!\n";
+    let options = ParseOptions {
+        assemble_buses: true,
+        ..ParseOptions::default()
+    };
+    let vcd = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+    let top = vcd.root_scopes_by_idx()[0];
+    assert_eq!(vcd.get_children_signal_idxs(top).len(), 2);
+}
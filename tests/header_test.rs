@@ -0,0 +1,40 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::fs::File;
+
+mod files;
+use files::FILES;
+
+#[test]
+fn header_lists_hierarchy_without_values() {
+    let file_name = "tests/vcd-files/icarus/CPU.vcd";
+    let header = fastwave_backend::parse_vcd_header_from_path(file_name).unwrap();
+    let full = fastwave_backend::parse_vcd_from_path(file_name).unwrap();
+
+    let (vcd, full) = (header.vcd(), &full);
+    assert_eq!(vcd.root_scopes_by_idx(), full.root_scopes_by_idx());
+    for scope_idx in vcd.root_scopes_by_idx() {
+        assert_eq!(
+            vcd.scope_name_by_idx(scope_idx),
+            full.scope_name_by_idx(scope_idx)
+        );
+        assert_eq!(
+            vcd.get_children_signal_idxs(scope_idx),
+            full.get_children_signal_idxs(scope_idx)
+        );
+    }
+    assert_eq!(vcd.max_timestamp(), &None);
+    assert!(full.max_timestamp().is_some());
+}
+
+#[test]
+fn finished_header_matches_full_parse() {
+    for file_name in FILES {
+        let header = fastwave_backend::parse_vcd_header(File::open(file_name).unwrap()).unwrap();
+        let finished = header.finish().unwrap();
+        let full = fastwave_backend::parse_vcd_from_path(file_name).unwrap();
+        assert_eq!(format!("{finished:?}"), format!("{full:?}"), "{file_name}");
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd_from_bytes_with_options, parse_vcd_header, BigUint, ParseOptions, ScopeIdx,
+    SignalValue, VCD,
+};
+
+const DUMP: &str = "$timescale 1 ns $end\n\
+    $scope module top $end\n\
+    $var wire 1 ! clk $end\n\
+    $var wire 1 \" core.alu.valid $end\n\
+    $var wire 1 # core/alu/ready $end\n\
+    $var wire 4 $ \\bus.x $end\n\
+    $var wire 1 % \\weird.name .flag $end\n\
+    $var wire 1 & broken..name $end\n\
+    $scope module core $end\n\
+    $var wire 1 ' busy $end\n\
+    $upscope $end\n\
+    $upscope $end\n\
+    $enddefinitions $end\n\
+    #0\n\
+    0!\n\
+    1\"\n";
+
+fn options(split_hierarchical_names: bool) -> ParseOptions {
+    ParseOptions {
+        split_hierarchical_names,
+        ..ParseOptions::default()
+    }
+}
+
+fn parse(split_hierarchical_names: bool) -> VCD {
+    parse_vcd_from_bytes_with_options(DUMP.as_bytes(), &options(split_hierarchical_names)).unwrap()
+}
+
+fn signal_paths(vcd: &VCD, scope: ScopeIdx) -> Vec<String> {
+    vcd.get_children_signal_idxs(scope)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx).path().join("|"))
+        .collect()
+}
+
+fn child_scope(vcd: &VCD, parent: ScopeIdx, name: &str) -> ScopeIdx {
+    vcd.child_scopes_by_idx(parent)
+        .into_iter()
+        .find(|scope| vcd.scope_name_by_idx(*scope) == name)
+        .unwrap()
+}
+
+#[test]
+fn names_are_kept_whole_by_default() {
+    let vcd = parse(false);
+    let top = vcd.root_scopes_by_idx()[0];
+    assert_eq!(
+        signal_paths(&vcd, top),
+        [
+            "top|clk",
+            "top|core.alu.valid",
+            "top|core/alu/ready",
+            "top|\\bus.x",
+            "top|\\weird.name .flag",
+            "top|broken..name",
+        ]
+    );
+}
+
+#[test]
+fn hierarchical_names_are_split_into_scopes() {
+    let vcd = parse(true);
+    let top = vcd.root_scopes_by_idx()[0];
+    assert_eq!(
+        signal_paths(&vcd, top),
+        ["top|clk", "top|\\bus.x", "top|broken..name"]
+    );
+
+    // the declared `core` scope is reused
+    let core = child_scope(&vcd, top, "core");
+    assert_eq!(vcd.child_scopes_by_idx(top).len(), 2);
+    assert_eq!(signal_paths(&vcd, core), ["top|core|busy"]);
+
+    let alu = child_scope(&vcd, core, "alu");
+    assert_eq!(
+        signal_paths(&vcd, alu),
+        ["top|core|alu|valid", "top|core|alu|ready"]
+    );
+    let valid = vcd.signal_from_signal_idx(vcd.get_children_signal_idxs(alu)[0]);
+    assert_eq!(valid.name(), "valid");
+    assert_eq!(
+        valid
+            .query_val_on_tmln(&BigUint::from(0u8), &vcd)
+            .unwrap()
+            .current,
+        Some((BigUint::from(0u8), SignalValue::BigUint(BigUint::from(1u8))))
+    );
+
+    let weird = child_scope(&vcd, top, "\\weird.name");
+    assert_eq!(signal_paths(&vcd, weird), ["top|\\weird.name|flag"]);
+}
+
+#[test]
+fn hierarchical_names_are_split_when_finishing_a_header() {
+    let header = parse_vcd_header(DUMP.as_bytes()).unwrap();
+    let vcd = header.finish_with_options(&options(true)).unwrap();
+    let top = vcd.root_scopes_by_idx()[0];
+    assert_eq!(
+        signal_paths(&vcd, top),
+        ["top|clk", "top|\\bus.x", "top|broken..name"]
+    );
+
+    let alu = child_scope(&vcd, child_scope(&vcd, top, "core"), "alu");
+    assert_eq!(
+        signal_paths(&vcd, alu),
+        ["top|core|alu|valid", "top|core|alu|ready"]
+    );
+}
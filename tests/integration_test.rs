@@ -8,15 +8,52 @@ mod files;
 use files::*;
 
 #[test]
+#[allow(non_snake_case)]
 fn parse_all_VCDs() {
     // see if we can parse all signal trees successfully
     for file_name in FILES {
         let file = File::open(file_name).unwrap();
         let vcd = fastwave_backend::parse_vcd(file);
 
-        if !vcd.is_ok() {
+        if let Err(e) = vcd {
             dbg!(file_name);
-            vcd.unwrap();
+            panic!("{e}");
+        }
+    }
+}
+
+#[test]
+fn zero_copy_parse_matches_streaming_parse() {
+    // the memory mapped and borrowed slice entry points should build
+    // exactly the same VCD as the streaming one
+    for file_name in FILES {
+        let file = File::open(file_name).unwrap();
+        let streamed = fastwave_backend::parse_vcd(file).unwrap();
+        let mapped = fastwave_backend::parse_vcd_from_path(file_name).unwrap();
+        let bytes = std::fs::read(file_name).unwrap();
+        let borrowed = fastwave_backend::parse_vcd_from_bytes(&bytes).unwrap();
+
+        let streamed = format!("{streamed:?}");
+        assert_eq!(streamed, format!("{mapped:?}"), "{file_name}");
+        assert_eq!(streamed, format!("{borrowed:?}"), "{file_name}");
+    }
+}
+
+#[test]
+fn parallel_parse_matches_sequential_parse() {
+    for file_name in FILES {
+        let bytes = std::fs::read(file_name).unwrap();
+        let sequential = fastwave_backend::parse_vcd_from_bytes(&bytes).unwrap();
+        let sequential = format!("{sequential:?}");
+
+        for threads in [2, 3, 8, 64] {
+            let options = fastwave_backend::ParseOptions {
+                threads,
+                ..Default::default()
+            };
+            let parallel =
+                fastwave_backend::parse_vcd_from_bytes_with_options(&bytes, &options).unwrap();
+            assert_eq!(sequential, format!("{parallel:?}"), "{file_name}");
         }
     }
 }
@@ -0,0 +1,166 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd_from_path, parse_vcd_from_path_lazily, BigUint, ParseOptions, SignalIdx, SignalValue,
+    VCD,
+};
+
+mod files;
+use files::FILES;
+
+type Query = (Option<(BigUint, SignalValue)>, Option<BigUint>);
+
+fn query(vcd: &VCD, signal_idx: usize, time: &BigUint) -> Result<Query, String> {
+    vcd.signal_from_signal_idx(SignalIdx(signal_idx))
+        .query_val_on_tmln(time, vcd)
+        .map(|result| (result.current, result.next))
+        .map_err(|e| format!("{e:?}"))
+}
+
+fn sample_times(vcd: &VCD) -> Vec<BigUint> {
+    let max = vcd.max_timestamp().clone().unwrap_or_default();
+    vec![
+        BigUint::from(0u8),
+        &max / 3u8,
+        &max / 2u8,
+        max.clone(),
+        max + 1u8,
+    ]
+}
+
+fn signal_count(vcd: &VCD) -> usize {
+    // signal indices are handed out densely, so count the valid ones
+    let mut count = 0;
+    let mut scopes = vcd.root_scopes_by_idx();
+    while let Some(scope) = scopes.pop() {
+        count += vcd.get_children_signal_idxs(scope).len();
+        scopes.extend(vcd.child_scopes_by_idx(scope));
+    }
+    count
+}
+
+#[test]
+fn lazy_queries_match_eager_queries() {
+    for file_name in FILES {
+        let eager = parse_vcd_from_path(file_name).unwrap();
+        for threads in [1, 3] {
+            let options = ParseOptions {
+                threads,
+                ..Default::default()
+            };
+            let lazy = parse_vcd_from_path_lazily(file_name, &options, usize::MAX).unwrap();
+            assert_eq!(lazy.max_timestamp(), eager.max_timestamp(), "{file_name}");
+
+            let signals = signal_count(&eager);
+            let all: Vec<_> = (0..signals).map(SignalIdx).collect();
+            lazy.load_signals(&all).unwrap();
+
+            for time in sample_times(&eager) {
+                for signal_idx in 0..signals {
+                    assert_eq!(
+                        query(&lazy, signal_idx, &time),
+                        query(&eager, signal_idx, &time),
+                        "{file_name} signal {signal_idx} at {time}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn evicted_signals_are_decoded_again() {
+    let file_name = "tests/vcd-files/icarus/CPU.vcd";
+    let eager = parse_vcd_from_path(file_name).unwrap();
+    let lazy = parse_vcd_from_path_lazily(file_name, &ParseOptions::default(), 2).unwrap();
+
+    let time = eager.max_timestamp().clone().unwrap() / 2u8;
+    for _ in 0..2 {
+        for signal_idx in 0..signal_count(&eager).min(10) {
+            assert_eq!(
+                query(&lazy, signal_idx, &time),
+                query(&eager, signal_idx, &time)
+            );
+        }
+    }
+}
+
+/// A dump several times larger than the blocks the lazy parser splits its
+/// value changes into, with a signal that only changes in a few of them.
+fn long_dump() -> String {
+    let mut dump = String::from(
+        "$timescale 1ns $end\n\
+         $scope module top $end\n\
+         $var wire 1 ! clk $end\n\
+         $var wire 8 \" count $end\n\
+         $var wire 1 # rare $end\n\
+         $upscope $end\n\
+         $enddefinitions $end\n\
+         #0\n\
+         $dumpvars\n0!\nb0 \"\nx#\n$end\n",
+    );
+    for step in 1..20_000u32 {
+        dump += &format!("#{}\n{}!\n", step * 5, step % 2);
+        if step % 2 == 0 {
+            dump += &format!("b{:b} \"\n", (step / 2) % 256);
+        }
+        if step % 7919 == 0 {
+            dump += &format!("{}#\n", (step / 7919) % 2);
+        }
+    }
+    dump
+}
+
+#[test]
+fn lazy_loads_match_eager_loads_across_blocks() {
+    let dump = long_dump();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &dump).unwrap();
+    let eager = fastwave_backend::parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    for threads in [1, 3] {
+        let options = ParseOptions {
+            threads,
+            ..Default::default()
+        };
+        let lazy = parse_vcd_from_path_lazily(file.path(), &options, 3).unwrap();
+        assert_eq!(lazy.max_timestamp(), eager.max_timestamp());
+        assert_eq!(lazy.initial_values(), eager.initial_values());
+
+        for time in (0..100_010u32).step_by(997).map(BigUint::from) {
+            for signal_idx in 0..3 {
+                assert_eq!(
+                    query(&lazy, signal_idx, &time),
+                    query(&eager, signal_idx, &time),
+                    "signal {signal_idx} at {time}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn lazy_signals_left_out_by_the_filter_are_never_loaded() {
+    let dump = long_dump();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &dump).unwrap();
+    let eager = fastwave_backend::parse_vcd_from_bytes(dump.as_bytes()).unwrap();
+
+    let options = ParseOptions {
+        signal_filter: Some(fastwave_backend::SignalFilter::from_globs(["top.count"])),
+        ..Default::default()
+    };
+    let lazy = parse_vcd_from_path_lazily(file.path(), &options, 4).unwrap();
+
+    assert!(!lazy.signal_from_signal_idx(SignalIdx(0)).is_loaded(&lazy));
+    assert!(lazy.signal_from_signal_idx(SignalIdx(1)).is_loaded(&lazy));
+    assert_eq!(
+        query(&lazy, 0, &BigUint::from(10u8)),
+        Err("NotLoaded".into())
+    );
+    let time = BigUint::from(50_000u32);
+    assert_eq!(query(&lazy, 1, &time), query(&eager, 1, &time));
+    assert_eq!(lazy.initial_values(), &eager.initial_values()[1..2]);
+}
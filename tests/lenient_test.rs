@@ -0,0 +1,146 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes_with_options, parse_vcd_with_options, ParseOptions, Severity,
+    VCD,
+};
+
+mod common;
+use common::{num, value_at};
+
+fn lenient() -> ParseOptions {
+    ParseOptions {
+        lenient: true,
+        ..Default::default()
+    }
+}
+
+/// The severity, line and message of every diagnostic.
+fn diagnostics(vcd: &VCD) -> Vec<(Severity, usize, String)> {
+    vcd.diagnostics()
+        .iter()
+        .map(|diagnostic| {
+            let line = diagnostic.cursor().map_or(0, |cursor| cursor.line);
+            (diagnostic.severity, line, diagnostic.message())
+        })
+        .collect()
+}
+
+#[test]
+fn malformed_constructs_are_skipped_and_reported() {
+    let dump = "$scope module top $end\n\
+        $var wire 1 ! a $end\n\
+        $var $wyre 1 \" b $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n\
+        0! 1\"\n\
+        #1x\n\
+        1! 0\"\n\
+        #20\n\
+        b2 !\n\
+        0!\n";
+    assert!(parse_vcd(dump.as_bytes()).is_err());
+
+    let vcd = parse_vcd_with_options(dump.as_bytes(), &lenient()).unwrap();
+    assert_eq!(
+        diagnostics(&vcd),
+        vec![
+            (
+                Severity::Error,
+                3,
+                "found `$wyre` but expected a variable type".to_string()
+            ),
+            (Severity::Error, 7, "undeclared identifier `\"`".to_string()),
+            (Severity::Error, 8, "invalid timestamp `#1x`".to_string()),
+            (Severity::Error, 11, "invalid value `b2`".to_string()),
+        ]
+    );
+
+    // the value change after the bad timestamp is recorded against `#0`
+    assert_eq!(value_at(&vcd, 0, 0), num(1));
+    assert_eq!(value_at(&vcd, 0, 20), num(0));
+}
+
+#[test]
+fn parallel_parse_reports_the_same_diagnostics() {
+    let mut dump = "$scope module top $end $var wire 1 ! a $end $upscope $end \
+        $enddefinitions $end\n"
+        .to_string();
+    for time in 0..5000 {
+        dump.push_str(&format!("#{time}\n{}! 1?\n", time % 2));
+        if time % 1000 == 999 {
+            dump.push_str(&format!("bz{time} !\n"));
+        }
+    }
+
+    let sequential = parse_vcd_from_bytes_with_options(dump.as_bytes(), &lenient()).unwrap();
+    // every distinct problem is only reported once
+    assert_eq!(sequential.diagnostics().len(), 6);
+
+    let options = ParseOptions {
+        threads: 4,
+        ..lenient()
+    };
+    let parallel = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+    assert_eq!(format!("{sequential:?}"), format!("{parallel:?}"));
+}
+
+#[test]
+fn truncated_header_keeps_the_signals_declared_so_far() {
+    let dump = "$scope module top $end\n\
+        $var wire 1 ! a $end\n\
+        $var wire 8 \" b";
+    let vcd = parse_vcd_with_options(dump.as_bytes(), &lenient()).unwrap();
+    assert_eq!(
+        diagnostics(&vcd),
+        vec![(Severity::Warning, 3, "unexpected end of file".to_string())]
+    );
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    assert_eq!(vcd.get_children_signal_idxs(top).len(), 1);
+}
+
+#[test]
+fn values_that_dont_fit_their_signal_are_reported() {
+    let dump = "$scope module top $end\n\
+        $var wire 1 ! a $end\n\
+        $var wire 4 \" b $end\n\
+        $var string 1 # c $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n\
+        b101 !\n\
+        1\"\n\
+        #10\n\
+        b110 !\n";
+
+    // strict parses merely mark the signals as malformed
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    assert!(vcd.diagnostics().is_empty());
+
+    let vcd = parse_vcd_with_options(format!("{dump}0#\n").as_bytes(), &lenient()).unwrap();
+    assert_eq!(
+        diagnostics(&vcd),
+        vec![
+            (
+                Severity::Error,
+                8,
+                "value of width 3 for signal a of width 1".to_string()
+            ),
+            (
+                Severity::Error,
+                9,
+                "value of width 1 for signal b of width 4".to_string()
+            ),
+            (
+                Severity::Error,
+                12,
+                "value change for signal c, which was declared without a width".to_string()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,47 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{parse_vcd_header, parse_vcd_header_from_path};
+
+mod files;
+use files::{DATED_FILES, FILES};
+
+#[test]
+fn dates_of_every_simulator_are_parsed() {
+    for file_name in FILES {
+        let header = parse_vcd_header_from_path(file_name).unwrap();
+        let metadata = header.metadata();
+        assert!(metadata.raw_date.is_some(), "{file_name}");
+        assert!(
+            metadata.date.is_some(),
+            "{file_name}: {:?}",
+            metadata.raw_date
+        );
+    }
+
+    for (file_name, date) in DATED_FILES {
+        let header = parse_vcd_header_from_path(file_name).unwrap();
+        let parsed = header.metadata().date.unwrap();
+        assert_eq!(parsed.to_rfc3339(), date, "{file_name}");
+    }
+}
+
+#[test]
+fn dates_keep_their_text() {
+    let dump = "$date\n  Tue Sept  5 09:30:00 CEST 2023\n$end\n\
+        $scope module top $end $var wire 1 ! a $end $upscope $end \
+        $enddefinitions $end\n";
+    let header = parse_vcd_header(dump.as_bytes()).unwrap();
+    let metadata = header.metadata();
+    assert_eq!(metadata.date, None);
+    assert_eq!(
+        metadata.raw_date.as_deref(),
+        Some("Tue Sept 5 09:30:00 CEST 2023")
+    );
+
+    let dump = dump.replace("CEST", "+0200");
+    let header = parse_vcd_header(dump.as_bytes()).unwrap();
+    let date = header.metadata().date.unwrap();
+    assert_eq!(date.to_rfc3339(), "2023-09-05T07:30:00+00:00");
+}
@@ -0,0 +1,134 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::sync::{Arc, Mutex};
+
+use fastwave_backend::{
+    parse_vcd_from_bytes_with_options, parse_vcd_with_options, BigUint, CancellationToken,
+    ParseError, ParseOptions, ParseProgress, TimestampPolicy,
+};
+
+/// A dump of a few megabytes, so that progress is reported more than once.
+fn large_dump() -> String {
+    let mut dump = "$timescale 1ns $end $scope module top $end \
+        $var wire 1 ! a $end $var wire 8 \" b $end $upscope $end \
+        $enddefinitions $end\n"
+        .to_string();
+    for time in 0..200_000u32 {
+        dump.push_str(&format!("#{time}\n{}!\nb{:b} \"\n", time % 2, time % 256));
+    }
+    dump
+}
+
+fn recording_options(reports: &Arc<Mutex<Vec<ParseProgress>>>) -> ParseOptions {
+    let reports = reports.clone();
+    ParseOptions {
+        progress: Some(Arc::new(move |progress: &ParseProgress| {
+            reports.lock().unwrap().push(progress.clone())
+        })),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn progress_is_reported_up_to_the_whole_input() {
+    let dump = large_dump();
+    let reports = Arc::new(Mutex::new(vec![]));
+    parse_vcd_from_bytes_with_options(dump.as_bytes(), &recording_options(&reports)).unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert!(reports.len() > 2);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].bytes_consumed <= pair[1].bytes_consumed));
+
+    let last = reports.last().unwrap();
+    assert_eq!(last.bytes_consumed, dump.len());
+    assert_eq!(last.total_bytes, Some(dump.len()));
+    assert_eq!(last.signals_declared, 2);
+    assert_eq!(last.current_timestamp, Some(BigUint::from(199_999u32)));
+}
+
+#[test]
+fn falling_back_to_a_sequential_parse_reports_like_one() {
+    // time goes back to 0 every 1000 steps, so every chunk but the first
+    // starts with a timestamp that is dropped, and the parse falls back to
+    // parsing the whole dump on one thread
+    let mut dump = large_dump().replacen("#0\n", "#0\n1?\n", 1);
+    for time in 0..200_000u32 {
+        dump.push_str(&format!("#{}\n{}!\n", time % 1000, time % 2));
+    }
+    let parse = |threads| {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let options = ParseOptions {
+            threads,
+            lenient: true,
+            timestamp_policy: TimestampPolicy::Drop,
+            ..recording_options(&reports)
+        };
+        let vcd = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+        let reports = reports.lock().unwrap().clone();
+        (vcd, reports)
+    };
+    let (sequential, _) = parse(1);
+    let (parallel, reports) = parse(4);
+
+    assert_eq!(parallel.diagnostics(), sequential.diagnostics());
+    assert_eq!(parallel.diagnostics().len(), 1);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].bytes_consumed <= pair[1].bytes_consumed));
+    assert_eq!(reports.last().unwrap().bytes_consumed, dump.len());
+}
+
+#[test]
+fn streams_report_progress_without_a_total() {
+    let dump = large_dump();
+    let reports = Arc::new(Mutex::new(vec![]));
+    parse_vcd_with_options(dump.as_bytes(), &recording_options(&reports)).unwrap();
+
+    let reports = reports.lock().unwrap();
+    let last = reports.last().unwrap();
+    assert_eq!(last.bytes_consumed, dump.len());
+    assert_eq!(last.total_bytes, None);
+}
+
+#[test]
+fn cancelling_stops_the_parse() {
+    let dump = large_dump();
+    let cancellation = CancellationToken::new();
+    let reports = Arc::new(Mutex::new(0));
+
+    let token = cancellation.clone();
+    let counter = reports.clone();
+    let options = ParseOptions {
+        progress: Some(Arc::new(move |_: &ParseProgress| {
+            *counter.lock().unwrap() += 1;
+            token.cancel();
+        })),
+        cancellation: Some(cancellation.clone()),
+        ..Default::default()
+    };
+    let result = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options);
+    assert_eq!(result.unwrap_err(), ParseError::Cancelled);
+    // the header is reported before the token is cancelled, after which
+    // nothing else gets through
+    assert_eq!(*reports.lock().unwrap(), 1);
+}
+
+#[test]
+fn cancelling_stops_a_parallel_parse() {
+    let dump = large_dump();
+    let cancellation = CancellationToken::new();
+    let token = cancellation.clone();
+    // cancel once the header is parsed, so that the chunks see it
+    let options = ParseOptions {
+        threads: 4,
+        progress: Some(Arc::new(move |_: &ParseProgress| token.cancel())),
+        cancellation: Some(cancellation),
+        ..Default::default()
+    };
+    let result = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options);
+    assert_eq!(result.unwrap_err(), ParseError::Cancelled);
+}
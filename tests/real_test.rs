@@ -0,0 +1,92 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes_with_options, Cursor, ParseError, ParseOptions, SignalValue,
+};
+
+mod common;
+use common::value_at;
+
+/// ``events`` after a header declaring `level`, `when` and `state`.
+fn with_header(events: &str) -> String {
+    common::dump(
+        None,
+        &["real 64 ! level", "realtime 64 \" when", "string 1 # state"],
+        events,
+    )
+}
+
+#[test]
+fn reals_are_stored_as_floats() {
+    let dump = with_header(
+        "#0\n$dumpvars\nr0 !\nR-2.5e3 \"\nsidle #\n$end\n\
+         #10\nr3.25 !\nSbusy #\n#20\nrnan !\n",
+    );
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+
+    assert_eq!(value_at(&vcd, 0, 0), Some(SignalValue::Real(0.0)));
+    assert_eq!(value_at(&vcd, 1, 0), Some(SignalValue::Real(-2500.0)));
+    assert_eq!(value_at(&vcd, 0, 15), Some(SignalValue::Real(3.25)));
+    assert!(matches!(value_at(&vcd, 0, 20), Some(SignalValue::Real(real)) if real.is_nan()));
+
+    // strings, including those written with an uppercase `S`, are kept as is
+    assert_eq!(
+        value_at(&vcd, 2, 0),
+        Some(SignalValue::String("idle".to_string()))
+    );
+    assert_eq!(
+        value_at(&vcd, 2, 10),
+        Some(SignalValue::String("busy".to_string()))
+    );
+
+    assert_eq!(vcd.initial_values()[0].1, SignalValue::Real(0.0));
+}
+
+#[test]
+fn malformed_reals_are_reported() {
+    let dump = with_header("#0\nr1.5 !\n#10\nr1,5 !\n#20\nr2 !\n");
+    let error = parse_vcd(dump.as_bytes()).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::BadValue {
+            value: "r1,5".to_string(),
+            cursor: Cursor {
+                line: 10,
+                column: 1,
+                byte_offset: 154,
+            },
+        }
+    );
+
+    // the malformed value is dropped when parsing leniently
+    let options = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let vcd = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+    assert_eq!(vcd.diagnostics().len(), 1);
+    assert_eq!(value_at(&vcd, 0, 10), Some(SignalValue::Real(1.5)));
+    assert_eq!(value_at(&vcd, 0, 20), Some(SignalValue::Real(2.0)));
+}
+
+#[test]
+fn vectors_are_rejected_for_reals() {
+    let dump = with_header("#0\nr1.5 !\n#10\nb0101 !\n#20\nr2 !\n");
+    let error = parse_vcd(dump.as_bytes()).unwrap_err();
+    assert!(matches!(
+        error,
+        ParseError::BadValue { ref value, cursor } if value == "b0101" && cursor.line == 10
+    ));
+
+    // only the vector is dropped when parsing leniently
+    let options = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let vcd = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+    assert_eq!(vcd.diagnostics().len(), 1);
+    assert_eq!(value_at(&vcd, 0, 10), Some(SignalValue::Real(1.5)));
+    assert_eq!(value_at(&vcd, 0, 20), Some(SignalValue::Real(2.0)));
+}
@@ -0,0 +1,73 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{parse_vcd, parse_vcd_from_path, validate_vcd, ScopeType, VCD};
+
+/// The name and type of every scope, depth first.
+fn scope_types(vcd: &VCD) -> Vec<(String, ScopeType)> {
+    let mut scopes = vec![];
+    let mut stack: Vec<_> = vcd.root_scopes_by_idx().into_iter().rev().collect();
+    while let Some(scope) = stack.pop() {
+        scopes.push((
+            vcd.scope_name_by_idx(scope).clone(),
+            vcd.scope_type_by_idx(scope).clone(),
+        ));
+        stack.extend(vcd.child_scopes_by_idx(scope).into_iter().rev());
+    }
+    scopes
+}
+
+#[test]
+fn scope_types_are_recorded() {
+    let dump = "$scope module top $end\n\
+        $scope interface bus $end\n\
+        $upscope $end\n\
+        $scope vhdl_architecture arch $end\n\
+        $scope vhdl_record rec $end\n\
+        $upscope $end\n\
+        $upscope $end\n\
+        $scope begin blk $end\n\
+        $scope sequence seq $end\n\
+        $upscope $end\n\
+        $upscope $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n";
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    let scope_type = |name: &str, scope_type| (name.to_string(), scope_type);
+    assert_eq!(
+        scope_types(&vcd),
+        vec![
+            scope_type("top", ScopeType::Module),
+            scope_type("bus", ScopeType::Interface),
+            scope_type("arch", ScopeType::VhdlArchitecture),
+            scope_type("rec", ScopeType::VhdlRecord),
+            scope_type("blk", ScopeType::Begin),
+            scope_type("seq", ScopeType::Other("sequence".to_string())),
+        ]
+    );
+    assert_eq!(ScopeType::VhdlRecord.keyword(), "vhdl_record");
+
+    // only kinds we don't know of are worth a warning
+    let diagnostics = validate_vcd(dump.as_bytes()).unwrap();
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "found `sequence` but expected a scope type such as `module`, `begin`, `task`, \
+             `function` or `fork`"
+        ]
+    );
+}
+
+#[test]
+fn scope_types_of_real_dumps_are_known() {
+    let vcd = parse_vcd_from_path("./tests/vcd-files/questa-sim/test.vcd").unwrap();
+    let scopes = scope_types(&vcd);
+    assert!(scopes
+        .iter()
+        .all(|(_, scope_type)| !matches!(scope_type, ScopeType::Other(_))));
+    assert!(scopes
+        .iter()
+        .any(|(_, scope_type)| *scope_type != ScopeType::Module));
+}
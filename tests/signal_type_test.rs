@@ -0,0 +1,88 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{parse_vcd, validate_vcd, BigUint, SignalType, SignalValue};
+
+#[test]
+fn systemverilog_and_unknown_types_are_parsed() {
+    let dump = "$scope module top $end\n\
+        $var logic 4 ! l $end\n\
+        $var bit 1 \" b $end\n\
+        $var int 32 # i $end\n\
+        $var shortreal 1 $ sr $end\n\
+        $var enum 2 % state $end\n\
+        $var sparray 8 & mem $end\n\
+        $var widget 3 ' w $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n\
+        b1x01 !\n\
+        1\"\n\
+        b101010 #\n\
+        r1.5 $\n\
+        b10 %\n\
+        b11111111 &\n\
+        b101 '\n";
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    let [top] = vcd.root_scopes_by_idx()[..] else {
+        panic!("expected a single root scope");
+    };
+    let signals: Vec<_> = vcd
+        .get_children_signal_idxs(top)
+        .into_iter()
+        .map(|idx| vcd.signal_from_signal_idx(idx))
+        .collect();
+    let types: Vec<_> = signals
+        .iter()
+        .map(|signal| signal.signal_type().unwrap().clone())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            SignalType::Logic,
+            SignalType::Bit,
+            SignalType::Int,
+            SignalType::ShortReal,
+            SignalType::Enum,
+            SignalType::SparseArray,
+            SignalType::Other("widget".to_string()),
+        ]
+    );
+
+    let value = |idx: usize| {
+        signals[idx]
+            .query_val_on_tmln(&BigUint::from(0u32), &vcd)
+            .unwrap()
+            .current
+            .unwrap()
+            .1
+    };
+    assert_eq!(value(2), SignalValue::BigUint(BigUint::from(42u32)));
+    assert_eq!(value(3), SignalValue::Real(1.5));
+    assert_eq!(value(6), SignalValue::BigUint(BigUint::from(5u32)));
+
+    // only types we don't know of are worth a warning
+    let diagnostics = validate_vcd(dump.as_bytes()).unwrap();
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "found `widget` but expected a variable type such as `wire`, `reg`, `integer` or \
+              `logic`"
+        ]
+    );
+}
+
+#[test]
+fn types_know_their_semantics() {
+    assert!(SignalType::Logic.is_four_state());
+    assert!(!SignalType::Bit.is_four_state());
+    assert!(!SignalType::Int.is_four_state());
+    assert_eq!(SignalType::Byte.default_width(), Some(8));
+    assert_eq!(SignalType::ShortInt.default_width(), Some(16));
+    assert_eq!(SignalType::LongInt.default_width(), Some(64));
+    assert_eq!(SignalType::Wire.default_width(), Some(1));
+    assert_eq!(SignalType::Parameter.default_width(), None);
+    assert_eq!(SignalType::from_keyword("sparray").keyword(), "sparray");
+}
@@ -0,0 +1,120 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+
+use fastwave_backend::{tail_vcd_from_path, BigUint, ParseError};
+use tempfile::NamedTempFile;
+
+mod common;
+use common::{num, value_at};
+
+/// ``events`` after a header declaring `clk` and `count`.
+fn with_header(events: &str) -> String {
+    common::dump(Some("1ns"), &["wire 1 ! clk", "wire 4 \" count"], events)
+}
+
+#[test]
+fn poll_picks_up_appended_events() {
+    let path = NamedTempFile::new().unwrap().into_temp_path();
+    fs::write(&path, with_header("#0\n0!\nb0000 \"\n#5\n1!\n")).unwrap();
+
+    let mut tail = tail_vcd_from_path(&path).unwrap();
+    assert_eq!(tail.vcd().max_timestamp(), &Some(BigUint::from(5u32)));
+    assert_eq!(value_at(tail.vcd(), 0, 7), num(1));
+
+    // nothing new has been written yet
+    assert_eq!(tail.poll_new_events().unwrap(), 0);
+
+    // the simulator is in the middle of writing a line, which we should
+    // leave alone until it is complete
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    write!(file, "#10\n0!\nb0101 ").unwrap();
+    file.flush().unwrap();
+    assert!(tail.poll_new_events().unwrap() > 0);
+    assert_eq!(tail.vcd().max_timestamp(), &Some(BigUint::from(10u32)));
+    assert_eq!(value_at(tail.vcd(), 0, 12), num(0));
+    assert_eq!(value_at(tail.vcd(), 1, 12), num(0));
+
+    write!(file, "\"\n#20\n1!\n").unwrap();
+    file.flush().unwrap();
+    assert!(tail.poll_new_events().unwrap() > 0);
+    assert_eq!(tail.vcd().max_timestamp(), &Some(BigUint::from(20u32)));
+    assert_eq!(value_at(tail.vcd(), 1, 12), num(5));
+    assert_eq!(value_at(tail.vcd(), 0, 25), num(1));
+}
+
+#[test]
+fn tailing_requires_a_complete_header() {
+    let path = NamedTempFile::new().unwrap().into_temp_path();
+    fs::write(&path, "$timescale 1ns $end\n$scope module top $end\n").unwrap();
+
+    assert!(tail_vcd_from_path(&path).is_err());
+}
+
+fn append(path: &std::path::Path, text: &str) -> File {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn blocks_going_back_in_time_are_merged_into_place() {
+    let path = NamedTempFile::new().unwrap().into_temp_path();
+    let dump = with_header("#0\n0!\nb0000 \"\n#10\n1!\nb0100 \"\n#5\nb0001 \"\n");
+    fs::write(&path, dump).unwrap();
+    let mut tail = tail_vcd_from_path(&path).unwrap();
+    assert_eq!(value_at(tail.vcd(), 1, 7), num(1));
+    assert_eq!(value_at(tail.vcd(), 1, 12), num(4));
+
+    // the block at `#5` carries on
+    append(&path, "b0010 \"\n");
+    assert!(tail.poll_new_events().unwrap() > 0);
+    assert_eq!(value_at(tail.vcd(), 1, 7), num(2));
+    assert_eq!(value_at(tail.vcd(), 1, 12), num(4));
+
+    append(&path, "#3\n1!\n#20\n0!\n");
+    assert!(tail.poll_new_events().unwrap() > 0);
+    assert_eq!(value_at(tail.vcd(), 0, 3), num(1));
+    assert_eq!(value_at(tail.vcd(), 0, 12), num(1));
+    assert_eq!(value_at(tail.vcd(), 0, 25), num(0));
+}
+
+#[test]
+fn values_split_across_writes_wait_for_their_identifier() {
+    let path = NamedTempFile::new().unwrap().into_temp_path();
+    fs::write(&path, with_header("#0\n0!\nb0011\n")).unwrap();
+
+    // the identifier of the vector hasn't been written yet
+    let mut tail = tail_vcd_from_path(&path).unwrap();
+    assert_eq!(value_at(tail.vcd(), 1, 0), None);
+
+    append(&path, "\"\n#5\n1!\n");
+    assert!(tail.poll_new_events().unwrap() > 0);
+    assert_eq!(value_at(tail.vcd(), 1, 0), num(3));
+    assert_eq!(value_at(tail.vcd(), 0, 5), num(1));
+}
+
+#[test]
+fn polls_carry_on_after_a_failure() {
+    let path = NamedTempFile::new().unwrap().into_temp_path();
+    fs::write(&path, with_header("#0\n0!\n")).unwrap();
+    let mut tail = tail_vcd_from_path(&path).unwrap();
+
+    append(&path, "#10\n1!\n#15\n0!\n1?\n");
+    assert!(matches!(
+        tail.poll_new_events(),
+        Err(ParseError::UndeclaredIdentifier { .. })
+    ));
+    assert_eq!(tail.vcd().max_timestamp(), &Some(BigUint::from(15u32)));
+
+    // the value changes before the failure aren't recorded a second time
+    append(&path, "#20\n1!\n");
+    assert!(tail.poll_new_events().unwrap() > 0);
+    assert_eq!(tail.vcd().max_timestamp(), &Some(BigUint::from(20u32)));
+    assert_eq!(value_at(tail.vcd(), 0, 12), num(1));
+    assert_eq!(value_at(tail.vcd(), 0, 17), num(0));
+    assert_eq!(value_at(tail.vcd(), 0, 22), num(1));
+}
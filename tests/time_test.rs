@@ -0,0 +1,133 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_header_from_path, parse_vcd_with_options, validate_vcd, BigUint,
+    ParseError, ParseOptions, Severity, Time, TimeScale, TimeUnit,
+};
+
+mod files;
+use files::FILES;
+
+fn time(text: &str) -> Time {
+    text.parse().unwrap()
+}
+
+fn lenient() -> ParseOptions {
+    ParseOptions {
+        lenient: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn timescales_of_every_simulator_are_parsed() {
+    for file_name in FILES {
+        let header = parse_vcd_header_from_path(file_name).unwrap();
+        assert!(header.metadata().timescale.is_some(), "{file_name}");
+    }
+}
+
+#[test]
+fn times_are_formatted_in_the_largest_unit() {
+    let ten_ps = TimeScale::new(10, TimeUnit::Ps).unwrap();
+    assert_eq!(
+        ten_ps.to_time(&BigUint::from(1250u32)).to_string(),
+        "12.5 ns"
+    );
+    assert_eq!(ten_ps.to_time(&BigUint::from(0u32)).to_string(), "0 s");
+    assert_eq!(time("-3.2us").to_string(), "-3.2 μs");
+    assert_eq!(time("1000 ms").to_string(), "1 s");
+    assert_eq!(time("0.000001 ns").to_string(), "1 fs");
+    assert_eq!(time("1.5 s").as_unit(TimeUnit::Ms), 1500.0);
+}
+
+#[test]
+fn bad_times_are_rejected() {
+    for text in ["", "ns", "1", "1.5 fs", "1.2.3 ns", "1 hours", "--1 ns"] {
+        assert!(text.parse::<Time>().is_err(), "{text}");
+    }
+    assert!(TimeScale::new(3, TimeUnit::Ns).is_none());
+}
+
+#[test]
+fn times_are_converted_to_ticks_around_the_timezero() {
+    let dump = "$timescale 100ps $end\n\
+        $timezero -20 $end\n\
+        $scope module top $end\n\
+        $var wire 1 ! a $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n0!\n#52\n1!\n";
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    let metadata = &vcd.metadata;
+    assert_eq!(metadata.timezero, Some(-20));
+
+    assert_eq!(metadata.time_at(&BigUint::from(52u32)), Some(time("3.2ns")));
+    // times within a tick fall in that tick
+    assert_eq!(
+        metadata.tick_at(&time("3.29 ns")),
+        Some(BigUint::from(52u32))
+    );
+    assert_eq!(metadata.tick_at(&time("-2 ns")), Some(BigUint::from(0u32)));
+    assert_eq!(metadata.tick_at(&time("-2.1 ns")), None);
+}
+
+#[test]
+fn nonstandard_timescales_are_reported() {
+    let dump = "$timescale 5 ns $end\n\
+        $scope module top $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n";
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    assert_eq!(vcd.metadata.timescale, None);
+    let [diagnostic] = &validate_vcd(dump.as_bytes()).unwrap()[..] else {
+        panic!("expected a single diagnostic");
+    };
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert!(matches!(
+        diagnostic.error,
+        ParseError::BadTimescale { ref timescale, .. } if timescale == "5 ns"
+    ));
+}
+
+#[test]
+fn nonstandard_timescales_dont_fail_the_parse() {
+    for section in ["$timescale 1000 ps $end", "$timescale 1 jiffy $end"] {
+        let dump =
+            format!("{section}\n$scope module top $end\n$upscope $end\n$enddefinitions $end\n");
+        let vcd = parse_vcd(dump.as_bytes()).unwrap();
+        assert_eq!(vcd.metadata.timescale, None);
+
+        let vcd = parse_vcd_with_options(dump.as_bytes(), &lenient()).unwrap();
+        assert_eq!(vcd.metadata.timescale, None);
+        let [diagnostic] = vcd.diagnostics() else {
+            panic!("expected a single diagnostic");
+        };
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert!(matches!(
+            diagnostic.error,
+            ParseError::BadTimescale { cursor, .. } if cursor.line == 1
+        ));
+    }
+
+    let dump = "$timezero soon $end\n$scope module top $end\n$upscope $end\n$enddefinitions $end\n";
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    assert_eq!(vcd.metadata.timezero, None);
+}
+
+#[test]
+fn timescales_are_kept_when_only_their_end_is_malformed() {
+    let dump = "$timescale 10 ns extra $end\n\
+        $timezero 5 extra $end\n\
+        $scope module top $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n";
+    for options in [ParseOptions::default(), lenient()] {
+        let vcd = parse_vcd_with_options(dump.as_bytes(), &options).unwrap();
+        assert_eq!(vcd.metadata.timescale, TimeScale::new(10, TimeUnit::Ns));
+        assert_eq!(vcd.metadata.timezero, Some(5));
+    }
+    assert_eq!(validate_vcd(dump.as_bytes()).unwrap().len(), 2);
+}
@@ -0,0 +1,110 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{
+    parse_vcd, parse_vcd_from_bytes_with_options, parse_vcd_with_options, BigUint, ParseError,
+    ParseOptions, TimestampPolicy, VCD,
+};
+
+mod common;
+
+/// ``events`` after a header declaring `a`.
+fn with_header(events: &str) -> String {
+    common::dump(None, &["wire 8 ! a"], events)
+}
+
+fn with_policy(timestamp_policy: TimestampPolicy) -> ParseOptions {
+    ParseOptions {
+        timestamp_policy,
+        ..Default::default()
+    }
+}
+
+/// The value of the only signal at ``time``, as a number.
+fn value_at(vcd: &VCD, time: u32) -> String {
+    format!("{:?}", common::value_at(vcd, 0, time).unwrap())
+}
+
+#[test]
+fn time_going_backwards_is_sorted_by_default() {
+    // out of order dumps were always accepted, so they still are
+    assert_eq!(
+        ParseOptions::default().timestamp_policy,
+        TimestampPolicy::Sort
+    );
+    assert_eq!(TimestampPolicy::default(), TimestampPolicy::Sort);
+
+    let dump = with_header("#0\nb0 !\n#10\nb1 !\n#5\nb10 !\n");
+    let vcd = parse_vcd(dump.as_bytes()).unwrap();
+    assert_eq!(value_at(&vcd, 7), "BigUint(2)");
+    assert_eq!(value_at(&vcd, 10), "BigUint(1)");
+}
+
+#[test]
+fn time_going_backwards_can_be_rejected() {
+    let dump = with_header("#0\nb0 !\n#10\nb1 !\n#5\nb10 !\n");
+    let error =
+        parse_vcd_from_bytes_with_options(dump.as_bytes(), &with_policy(TimestampPolicy::Reject))
+            .unwrap_err();
+    assert!(matches!(
+        error,
+        ParseError::TimestampOutOfOrder { ref timestamp, ref previous, cursor }
+            if timestamp == "#5" && previous == "#10" && cursor.line == 9
+    ));
+}
+
+#[test]
+fn repeated_timestamps_share_an_entry_on_the_timeline() {
+    let repeated = with_header("#0\nb0 !\n#10\nb1 !\n#10\n#10\nb10 !\n#20\nb11 !\n");
+    let collapsed = with_header("#0\nb0 !\n#10\nb1 !\nb10 !\n#20\nb11 !\n");
+    let repeated = parse_vcd(repeated.as_bytes()).unwrap();
+    let collapsed = parse_vcd(collapsed.as_bytes()).unwrap();
+    assert_eq!(format!("{repeated:?}"), format!("{collapsed:?}"));
+}
+
+#[test]
+fn out_of_order_blocks_are_sorted_or_dropped() {
+    let dump = with_header("#0\nb0 !\n#20\nb1 !\n#10\nb10 !\n#30\nb11 !\n#20\nb100 !\n");
+
+    let sorted =
+        parse_vcd_with_options(dump.as_bytes(), &with_policy(TimestampPolicy::Sort)).unwrap();
+    assert_eq!(value_at(&sorted, 15), "BigUint(2)");
+    // the later change at #20 wins
+    assert_eq!(value_at(&sorted, 25), "BigUint(4)");
+    assert_eq!(value_at(&sorted, 30), "BigUint(3)");
+    assert_eq!(sorted.max_timestamp(), &Some(BigUint::from(30u32)));
+
+    let dropped =
+        parse_vcd_with_options(dump.as_bytes(), &with_policy(TimestampPolicy::Drop)).unwrap();
+    assert_eq!(value_at(&dropped, 15), "BigUint(0)");
+    assert_eq!(value_at(&dropped, 25), "BigUint(1)");
+    assert_eq!(value_at(&dropped, 30), "BigUint(3)");
+}
+
+#[test]
+fn parallel_parses_handle_timestamps_like_sequential_ones() {
+    let mut dump = with_header("");
+    for time in 0..4000u32 {
+        // go back in time within and across chunks, and repeat a timestamp
+        // every now and then
+        let time = match time % 1000 {
+            500 => time - 300,
+            501 => time - 1,
+            _ => time % 2500,
+        };
+        dump.push_str(&format!("#{time}\nb{:b} !\n", time % 256));
+    }
+
+    for timestamp_policy in [TimestampPolicy::Sort, TimestampPolicy::Drop] {
+        let sequential =
+            parse_vcd_from_bytes_with_options(dump.as_bytes(), &with_policy(timestamp_policy))
+                .unwrap();
+        let options = ParseOptions {
+            threads: 4,
+            ..with_policy(timestamp_policy)
+        };
+        let parallel = parse_vcd_from_bytes_with_options(dump.as_bytes(), &options).unwrap();
+        assert_eq!(format!("{sequential:?}"), format!("{parallel:?}"));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (C) 2022 Yehowshua Immanuel
+// This program is distributed under both the GPLV3 license
+// and the YEHOWSHUA license, both of which can be found at
+// the root of the folder containing the sources for this program.
+use fastwave_backend::{validate_vcd, validate_vcd_from_path, Severity};
+
+/// The severity, line and message of every problem found in ``dump``.
+fn lint(dump: &str) -> Vec<(Severity, usize, String)> {
+    validate_vcd(dump.as_bytes())
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            let line = diagnostic.cursor().map_or(0, |cursor| cursor.line);
+            (diagnostic.severity, line, diagnostic.message())
+        })
+        .collect()
+}
+
+#[test]
+fn conforming_dumps_have_no_problems() {
+    let diagnostics = validate_vcd_from_path("./tests/vcd-files/icarus/CPU.vcd").unwrap();
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn every_violation_is_reported_with_its_line() {
+    let dump = "$timescale 1 ns $end\n\
+        $scope module top $end\n\
+        $var wire 1 ! a $end\n\
+        $var wire 4 \" b $end\n\
+        $scope module inner $end\n\
+        $enddefinitions $end\n\
+        $dumpvars 0! b0 \" $end\n\
+        #10\n\
+        b10101 \" 1!\n\
+        #5\n\
+        1?\n\
+        $var\n\
+        #10\n\
+        0!\n";
+    assert_eq!(
+        lint(dump),
+        vec![
+            (
+                Severity::Warning,
+                6,
+                "found `$enddefinitions` but expected `$scope`, `$var`, `$comment`, or \
+                 `$upscope`"
+                    .to_string()
+            ),
+            (
+                Severity::Error,
+                9,
+                "value of width 5 for signal b of width 4".to_string()
+            ),
+            (
+                Severity::Error,
+                10,
+                "timestamp `#5` doesn't come after `#10`".to_string()
+            ),
+            (Severity::Error, 11, "undeclared identifier `?`".to_string()),
+            (
+                Severity::Error,
+                12,
+                "found `$var` but expected one of [\"$comment\", \"$dumpall\", \
+                 \"$dumpoff\", \"$dumpon\", \"$dumpvars\"]"
+                    .to_string()
+            ),
+            (
+                Severity::Warning,
+                13,
+                "timestamp `#10` doesn't come after `#10`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn tolerated_constructs_are_warned_about() {
+    let dump = "$scope module top $end\n\
+        $var wire 1 ! a $end\n\
+        $var wire 4 \" b $end\n\
+        $upscope $end\n\
+        $enddefinitions $end\n\
+        #0\n\
+        $dumpvars 0! bzz \"\n\
+        #5\n\
+        u! B1uW0 \"\n\
+        $end\n\
+        $comment never closed\n";
+    assert_eq!(
+        lint(dump),
+        vec![
+            (
+                Severity::Warning,
+                8,
+                "found `#5` but expected `$end` to close `$dumpvars`".to_string()
+            ),
+            (Severity::Warning, 9, "invalid value `u!`".to_string()),
+            (Severity::Warning, 9, "invalid value `B1uW0`".to_string()),
+            (
+                Severity::Error,
+                10,
+                "found `$end` but expected a simulation keyword before it".to_string()
+            ),
+            (Severity::Error, 12, "unexpected end of file".to_string()),
+        ]
+    );
+}